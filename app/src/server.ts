@@ -30,7 +30,7 @@ app.get('/health', (req: Request, res: Response) => {
 // Initialize campaign after on-chain launch
 app.post('/campaigns/initialize', async (req: Request, res: Response, next: NextFunction) => {
   try {
-    const { tx, launcher, mint, fund } = req.body;
+    const { tx, launcher, mint, seed, fund } = req.body;
     
     if (!tx) {
       return res.status(400).json({ error: 'Missing required parameters' });
@@ -39,6 +39,7 @@ app.post('/campaigns/initialize', async (req: Request, res: Response, next: Next
     const campaign = campaignService.initializeCampaign({
       launcher,
       mint,
+      seed,
       fund,
       merkleRoot,
       initialRewards
@@ -74,10 +75,10 @@ app.get('/campaigns/:id', (req: Request, res: Response, next: NextFunction) => {
   }
 });
 
-// Get campaign by launcher and mint
-app.get('/campaigns/pda/:launcher/:mint', (req: Request, res: Response, next: NextFunction) => {
+// Get campaign by launcher, mint and launch seed
+app.get('/campaigns/pda/:launcher/:mint/:seed', (req: Request, res: Response, next: NextFunction) => {
   try {
-    const campaign = campaignService.getCampaignByPDA(req.params.launcher, req.params.mint);
+    const campaign = campaignService.getCampaignByPDA(req.params.launcher, req.params.mint, req.params.seed);
     
     if (!campaign) {
       return res.status(404).json({ error: 'Campaign not found' });
@@ -258,7 +259,7 @@ app.listen(PORT, () => {
   console.log('  POST   /campaigns/initialize');
   console.log('  GET    /campaigns');
   console.log('  GET    /campaigns/:id');
-  console.log('  GET    /campaigns/pda/:launcher/:mint');
+  console.log('  GET    /campaigns/pda/:launcher/:mint/:seed');
   console.log('  POST   /campaigns/:id/distribute');
   console.log('  POST   /campaigns/:id/update-merkle');
   console.log('  GET    /campaigns/:id/proof/:address');
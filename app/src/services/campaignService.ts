@@ -9,6 +9,7 @@ import fs from "fs";
 export interface CampaignInitParams {
   launcher: string;
   mint: string;
+  seed: string;
   fund: string;
   sig: string;
 }
@@ -59,33 +60,41 @@ const provider = new anchor.AnchorProvider(connection, wallet, {
 });
 anchor.setProvider(provider);
 const program = new anchor.Program(idl, provider);
+const [configPDA] = PublicKey.findProgramAddressSync([Buffer.from("config")], program.programId);
+
+// The backend tree only builds sha256 trees with prefixed leaves and nodes
+const HASH_ALGO_SHA256 = 0;
+const HASH_VERSION_PREFIXED = 1;
+
+function campaignAddress(launcher: PublicKey, mint: PublicKey, seed: anchor.BN): PublicKey {
+  const [campaignPDA, _campaignBump] = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("campaign"),
+      launcher.toBuffer(),
+      mint.toBuffer(),
+      seed.toArrayLike(Buffer, "le", 8),
+    ],
+    program.programId
+  );
+  return campaignPDA;
+}
 
 export class CampaignService {
   // Initialize campaign after launch
   async initializeCampaign(params: CampaignInitParams) {
-    const { launcher, mint, fund } = params;
+    const { launcher, mint, seed, fund } = params;
     const mintKey = new PublicKey(mint);
     const launcherKey = new PublicKey(launcher);
+    const campaignPDA = campaignAddress(launcherKey, mintKey, new anchor.BN(seed));
 
-    const [campaignPDA, _campaignBump] = PublicKey.findProgramAddressSync(
-      [
-        Buffer.from("campaign"),
-        launcherKey.toBuffer(),
-        mintKey.toBuffer(),
-      ],
-      program.programId
-    ); 
-    
-    // Create initial merkle tree with launcher as first participant
-    const initialLeaves: MerkleLeaf[] = [{
-      address: launcher,
-      amount: BigInt(params.fund),
-      nonce: BigInt(Math.floor(Math.random() * 1000000)),
-      index: 0
-    }];
-
-    // Add placeholder leaves to match initial size (32 leaves)
-    for (let i = 1; i < 32; i++) {
+    const campaignState = await program.account.campaignState.fetch(campaignPDA);
+    if (campaignState.hashAlgo !== HASH_ALGO_SHA256 || campaignState.hashVersion !== HASH_VERSION_PREFIXED) {
+      throw new Error('Campaign was launched with a hash scheme the backend tree does not build');
+    }
+
+    // Index 0 is the campaign's unallocated pool, so every leaf starts as a placeholder
+    const initialLeaves: MerkleLeaf[] = [];
+    for (let i = 0; i < 32; i++) {
       initialLeaves.push({
         address: PublicKey.default.toString(),
         amount: 0n,
@@ -108,18 +117,12 @@ export class CampaignService {
       updatedAt: new Date()
     };
 
-    // Add launcher as first participant
-    campaign.participants.set(launcher, {
-      address: launcher,
-      amount: BigInt(fund),
-      nonce: initialLeaves[0].nonce,
-      index: 0,
-    });
-
     await program.methods
       .lock()
       .accounts({
         distributor: distributor.publicKey,
+        config: configPDA,
+        launcher,
         campaign: campaignPDA,
         mint
       })
@@ -134,6 +137,7 @@ export class CampaignService {
       .update(Array.from(merkleTree.getRoot()), [])
       .accounts({
         distributor: distributor.publicKey,
+        config: configPDA,
         launcher,
         campaign: campaignPDA,
         mint
@@ -147,18 +151,9 @@ export class CampaignService {
     return db.getCampaign(campaignId);
   }
 
-  // Get campaign by launcher and mint
-  getCampaignByPDA(launcher: string, mint: string): Campaign | undefined {
-    const mintKey = new PublicKey(mint);
-    const launcherKey = new PublicKey(launcher);
-    const [campaignPDA, _campaignBump] = PublicKey.findProgramAddressSync(
-      [
-        Buffer.from("campaign"),
-        launcherKey.toBuffer(),
-        mintKey.toBuffer(),
-      ],
-      program.programId
-    ); 
+  // Get campaign by launcher, mint and launch seed
+  getCampaignByPDA(launcher: string, mint: string, seed: string): Campaign | undefined {
+    const campaignPDA = campaignAddress(new PublicKey(launcher), new PublicKey(mint), new anchor.BN(seed));
     return db.getCampaign(campaignPDA.toString());
   }
 
@@ -184,6 +179,8 @@ export class CampaignService {
       .lock()
       .accounts({
         distributor: distributor.publicKey,
+        config: configPDA,
+        launcher: new PublicKey(campaign.launcher),
         campaign: new PublicKey(campaign.id),
         mint: new PublicKey(campaign.mint),
       })
@@ -204,8 +201,8 @@ export class CampaignService {
         db.updateParticipantReward(params.campaignId, dist.address, newAmount);
         batchUpdates.push({ index: existingParticipant.index, amount: newAmount });
       } else {
-        // Find next available index
-        let nextIndex = campaign.participants.size;
+        // Find next available index, participants start at 1
+        let nextIndex = campaign.participants.size + 1;
         
         // Add new participant
         const participant: ParticipantInfo = {
@@ -290,7 +287,7 @@ export class CampaignService {
 
     // Add new participants
     if (params.newParticipants) {
-      const requiredSize = campaign.participants.size + params.newParticipants.length;
+      const requiredSize = campaign.participants.size + 1 + params.newParticipants.length;
       if (requiredSize > currentSize) {
         // Need to extend the tree
         needsExtension = true;
@@ -299,7 +296,7 @@ export class CampaignService {
       }
 
       for (const newParticipant of params.newParticipants) {
-        const index = campaign.participants.size;
+        const index = campaign.participants.size + 1;
         const participant: ParticipantInfo = {
           address: newParticipant.address,
           amount: BigInt(newParticipant.amount),
@@ -2,6 +2,10 @@ import { sha256 } from 'js-sha256';
 import { PublicKey } from '@solana/web3.js';
 import BN from 'bn.js';
 
+// Domain separation prefixes matching the program's prefixed hash version
+const LEAF_PREFIX = Buffer.from([0x00]);
+const NODE_PREFIX = Buffer.from([0x01]);
+
 export interface MerkleLeaf {
   address: string;
   amount: bigint;
@@ -29,6 +33,7 @@ export class MerkleTree {
     return Buffer.from(
       sha256.array(
         Buffer.concat([
+          LEAF_PREFIX,
           address.toBuffer(),
           amountBuffer,
           indexBuffer,
@@ -40,7 +45,7 @@ export class MerkleTree {
 
   private hashPair(left: Buffer, right: Buffer): Buffer {
     return Buffer.from(
-      sha256.array(Buffer.concat([left, right]))
+      sha256.array(Buffer.concat([NODE_PREFIX, left, right]))
     );
   }
 
@@ -0,0 +1,247 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Context, Result};
+use commi_merkle_client::tree::{Leaf, LeafHasher, MerkleTree};
+use commi_merkle_client::CampaignState;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+// One row of an allocation file: `amount` is added to whatever the address already holds
+#[derive(Clone, Debug, Deserialize)]
+pub struct Allocation {
+  pub address: String,
+  pub amount: u64,
+}
+
+// Reads a JSON array of {address, amount} or a CSV with `address,amount` rows
+pub fn read_allocations(path: &Path) -> Result<Vec<Allocation>> {
+  let text = std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+  if path.extension().is_some_and(|ext| ext == "json") {
+    return serde_json::from_str(&text).with_context(|| format!("parsing {}", path.display()));
+  }
+  let mut allocations = vec![];
+  for (line_no, line) in text.lines().enumerate() {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') || line.starts_with("address") {
+      continue;
+    }
+    let (address, amount) = line
+      .split_once(',')
+      .ok_or_else(|| anyhow!("line {}: expected `address,amount`", line_no + 1))?;
+    allocations.push(Allocation {
+      address: address.trim().to_string(),
+      amount: amount.trim().parse().with_context(|| format!("line {}: invalid amount", line_no + 1))?,
+    });
+  }
+  Ok(allocations)
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TreeLeaf {
+  pub index: u64,
+  pub address: String,
+  pub amount: u64,
+  pub nonce: u64,
+}
+
+// The operator's copy of the campaign tree; proofs are generated from it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TreeFile {
+  pub hash_algo: u8,
+  pub hash_version: u8,
+  pub size: usize,
+  pub root: String,
+  pub leaves: Vec<TreeLeaf>,
+}
+
+impl TreeFile {
+  pub fn load(path: &Path) -> Result<Self> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    serde_json::from_str(&text).with_context(|| format!("parsing {}", path.display()))
+  }
+
+  pub fn save(&self, path: &Path) -> Result<()> {
+    std::fs::write(path, serde_json::to_string_pretty(self)?).with_context(|| format!("writing {}", path.display()))
+  }
+
+  pub fn tree(&self) -> Result<MerkleTree> {
+    let leaves = self.leaves
+      .iter()
+      .map(|leaf| Ok(Leaf::new(parse_pubkey(&leaf.address)?.to_bytes(), leaf.amount, leaf.index, leaf.nonce)))
+      .collect::<Result<Vec<_>>>()?;
+    Ok(MerkleTree::with_size(LeafHasher::new(self.hash_algo, self.hash_version), leaves, self.size))
+  }
+
+  pub fn find(&self, address: &Pubkey) -> Option<&TreeLeaf> {
+    let address = address.to_string();
+    self.leaves.iter().find(|leaf| leaf.address == address)
+  }
+}
+
+pub fn parse_pubkey(address: &str) -> Result<Pubkey> {
+  Pubkey::from_str(address).map_err(|_| anyhow!("invalid address {address}"))
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+  bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn fresh_nonce(index: u64) -> u64 {
+  let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_nanos()).unwrap_or_default();
+  (nanos as u64).rotate_left(17) ^ index.wrapping_mul(0x9e37_79b9_7f4a_7c15)
+}
+
+// Merges a batch into the previous tree and returns the new tree together with the
+// `[user_idx, amount]` pairs `update` adds on-chain. Leaf amounts mirror the stored
+// rewards after the update; index 0 is the unallocated pool and never gets a leaf.
+pub fn apply_allocations(
+  campaign: &CampaignState,
+  previous: Option<TreeFile>,
+  allocations: &[Allocation],
+) -> Result<(TreeFile, Vec<[u64; 2]>)> {
+  let mut leaves: BTreeMap<u64, TreeLeaf> = previous
+    .map(|tree| tree.leaves.into_iter().filter(|leaf| leaf.index != 0).map(|leaf| (leaf.index, leaf)).collect())
+    .unwrap_or_default();
+  let mut additions: BTreeMap<u64, u64> = BTreeMap::new();
+
+  for allocation in allocations {
+    parse_pubkey(&allocation.address)?;
+    let existing = leaves.values().find(|leaf| leaf.address == allocation.address).map(|leaf| leaf.index);
+    let index = match existing {
+      Some(index) => index,
+      None => {
+        let index = (1..campaign.rewards.len() as u64)
+          .find(|index| !leaves.contains_key(index))
+          .ok_or_else(|| anyhow!("campaign is full, run `commi extend` first"))?;
+        leaves.insert(index, TreeLeaf { index, address: allocation.address.clone(), amount: 0, nonce: fresh_nonce(index) });
+        index
+      }
+    };
+    let total = additions.entry(index).or_default();
+    *total = total.checked_add(allocation.amount).ok_or_else(|| anyhow!("allocation overflow"))?;
+  }
+
+  let allocated = additions
+    .values()
+    .try_fold(0u64, |sum, amount| sum.checked_add(*amount))
+    .ok_or_else(|| anyhow!("allocation overflow"))?;
+  let unallocated = campaign.rewards.first().copied().unwrap_or_default();
+  if allocated > unallocated {
+    return Err(anyhow!("allocations exceed the {unallocated} unallocated tokens"));
+  }
+  for leaf in leaves.values_mut() {
+    let stored = *campaign.rewards
+      .get(leaf.index as usize)
+      .ok_or_else(|| anyhow!("index {} is outside the campaign, run `commi extend` first", leaf.index))?;
+    leaf.amount = stored
+      .checked_add(additions.get(&leaf.index).copied().unwrap_or_default())
+      .ok_or_else(|| anyhow!("allocation overflow at index {}", leaf.index))?;
+  }
+
+  let mut tree_file = TreeFile {
+    hash_algo: campaign.hash_algo,
+    hash_version: campaign.hash_version,
+    size: campaign.rewards.len(),
+    root: String::new(),
+    leaves: leaves.into_values().collect(),
+  };
+  tree_file.root = to_hex(&tree_file.tree()?.root());
+  let participants = additions.into_iter().map(|(index, amount)| [index, amount]).collect();
+  Ok((tree_file, participants))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn campaign(rewards: Vec<u64>) -> CampaignState {
+    let participants = rewards.len();
+    CampaignState {
+      launcher: Pubkey::new_unique(),
+      mint: Pubkey::new_unique(),
+      distributor: Pubkey::new_unique(),
+      fund: rewards.iter().fold(0u64, |sum, reward| sum.saturating_add(*reward)),
+      seed: 1,
+      start_ts: 0,
+      end_ts: i64::MAX,
+      claim_open_ts: 0,
+      claim_close_ts: 0,
+      vesting_cliff: 0,
+      vesting_duration: 0,
+      vesting_outstanding: 0,
+      realloc_rent: 0,
+      locked: 1,
+      closed: 0,
+      hash_version: 1,
+      hash_algo: 0,
+      merkle_root: [0u8; 32],
+      root_grace_slots: 0,
+      root_history_idx: 0,
+      root_history: Default::default(),
+      rewards,
+      claimed: vec![0; participants],
+    }
+  }
+
+  fn allocation(address: &Pubkey, amount: u64) -> Allocation {
+    Allocation { address: address.to_string(), amount }
+  }
+
+  #[test]
+  fn assigns_new_addresses_the_lowest_free_index_after_the_pool() {
+    let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let (tree, participants) =
+      apply_allocations(&campaign(vec![1_000, 0, 0, 0]), None, &[allocation(&alice, 100), allocation(&bob, 200)]).unwrap();
+
+    assert_eq!(participants, vec![[1, 100], [2, 200]]);
+    assert!(tree.leaves.iter().all(|leaf| leaf.index != 0));
+    assert_eq!(tree.find(&alice).unwrap().index, 1);
+    assert_eq!(tree.find(&bob).unwrap().index, 2);
+    assert_eq!(tree.root, to_hex(&tree.tree().unwrap().root()));
+  }
+
+  #[test]
+  fn merges_repeat_addresses_into_their_existing_leaf() {
+    let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let (previous, _) = apply_allocations(&campaign(vec![1_000, 0, 0, 0]), None, &[allocation(&alice, 100)]).unwrap();
+    let nonce = previous.find(&alice).unwrap().nonce;
+
+    // The first batch has landed, so the campaign now stores 100 at index 1
+    let (tree, participants) = apply_allocations(
+      &campaign(vec![900, 100, 0, 0]),
+      Some(previous),
+      &[allocation(&alice, 50), allocation(&bob, 25), allocation(&alice, 5)],
+    )
+    .unwrap();
+
+    assert_eq!(participants, vec![[1, 55], [2, 25]]);
+    let leaf = tree.find(&alice).unwrap();
+    assert_eq!((leaf.index, leaf.amount, leaf.nonce), (1, 155, nonce));
+    assert_eq!(tree.find(&bob).unwrap().amount, 25);
+  }
+
+  #[test]
+  fn rejects_a_batch_once_the_campaign_is_full() {
+    let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let error = apply_allocations(&campaign(vec![1_000, 0]), None, &[allocation(&alice, 1), allocation(&bob, 1)]).unwrap_err();
+    assert!(error.to_string().contains("campaign is full"));
+  }
+
+  #[test]
+  fn rejects_allocations_above_the_unallocated_pool() {
+    let alice = Pubkey::new_unique();
+    let error = apply_allocations(&campaign(vec![10, 0]), None, &[allocation(&alice, 11)]).unwrap_err();
+    assert!(error.to_string().contains("exceed"));
+  }
+
+  #[test]
+  fn rejects_leaf_amounts_that_overflow() {
+    let alice = Pubkey::new_unique();
+    let (previous, _) = apply_allocations(&campaign(vec![1, 0]), None, &[allocation(&alice, 1)]).unwrap();
+    let error = apply_allocations(&campaign(vec![1, u64::MAX]), Some(previous), &[allocation(&alice, 1)]).unwrap_err();
+    assert!(error.to_string().contains("overflow"));
+  }
+}
@@ -0,0 +1,325 @@
+mod allocation;
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::Engine;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use commi_merkle_client::accounts::{decode_campaign, decode_config, decode_fee_config};
+use commi_merkle_client::instructions::{decode_fee_quote, quote_fee, CampaignRef, ClaimParams, LaunchParams};
+use commi_merkle_client::pda::{config_address, fee_config_address};
+use commi_merkle_client::tree::{HASH_ALGO_KECCAK256, HASH_ALGO_SHA256};
+use commi_merkle_client::{CampaignSchedule, CampaignState};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+
+use crate::allocation::{apply_allocations, read_allocations, to_hex, TreeFile};
+
+#[derive(Parser)]
+#[command(name = "commi", about = "Operate commi-merkle campaigns")]
+struct Cli {
+  /// RPC endpoint
+  #[arg(long, short = 'u', env = "COMMI_RPC_URL", default_value = "http://127.0.0.1:8899", global = true)]
+  url: String,
+
+  /// Keypair signing and paying for transactions
+  #[arg(long, short = 'k', env = "COMMI_KEYPAIR", default_value = "~/.config/solana/id.json", global = true)]
+  keypair: String,
+
+  #[command(subcommand)]
+  command: Command,
+}
+
+#[derive(Args)]
+struct CampaignArgs {
+  #[arg(long)]
+  launcher: Pubkey,
+  #[arg(long)]
+  mint: Pubkey,
+  #[arg(long)]
+  seed: u64,
+  /// Token program owning the mint (SPL Token unless set)
+  #[arg(long)]
+  token_program: Option<Pubkey>,
+}
+
+impl CampaignArgs {
+  fn campaign(&self) -> CampaignRef {
+    let campaign = CampaignRef::new(self.launcher, self.mint, self.seed);
+    match self.token_program {
+      Some(token_program) => campaign.with_token_program(token_program),
+      None => campaign,
+    }
+  }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum HashAlgo {
+  Sha256,
+  Keccak256,
+}
+
+#[derive(Subcommand)]
+enum Command {
+  /// Launch a campaign funded from the keypair's token account
+  Launch {
+    #[arg(long)]
+    mint: Pubkey,
+    #[arg(long)]
+    seed: u64,
+    /// Raw token amount moved into the vault
+    #[arg(long)]
+    fund: u64,
+    #[arg(long)]
+    distributor: Pubkey,
+    #[arg(long)]
+    end_ts: i64,
+    #[arg(long, default_value_t = 0)]
+    start_ts: i64,
+    #[arg(long, default_value_t = 0)]
+    claim_open_ts: i64,
+    /// 0 keeps claims open until clawback
+    #[arg(long, default_value_t = 0)]
+    claim_close_ts: i64,
+    #[arg(long, default_value_t = 0)]
+    vesting_cliff: i64,
+    /// 0 pays claims out immediately
+    #[arg(long, default_value_t = 0)]
+    vesting_duration: i64,
+    #[arg(long, default_value_t = 0)]
+    root_grace_slots: u64,
+    #[arg(long, value_enum, default_value = "sha256")]
+    hash_algo: HashAlgo,
+    /// Whitelisted stablecoin paying the service fee instead of SOL
+    #[arg(long)]
+    fee_mint: Option<Pubkey>,
+    #[arg(long)]
+    token_program: Option<Pubkey>,
+  },
+  /// Print the lamport service fee a launch would charge right now
+  Quote {
+    /// Launcher to quote for, the keypair if omitted
+    #[arg(long)]
+    launcher: Option<Pubkey>,
+    /// Quote in this stablecoin's base units instead of lamports
+    #[arg(long)]
+    fee_mint: Option<Pubkey>,
+  },
+  /// Print the decoded campaign state
+  Show {
+    #[command(flatten)]
+    campaign: CampaignArgs,
+  },
+  /// Halt claims until the next update
+  Lock {
+    #[command(flatten)]
+    campaign: CampaignArgs,
+  },
+  /// Add an allocation batch, publish the new root and write the tree file
+  Update {
+    #[command(flatten)]
+    campaign: CampaignArgs,
+    /// JSON [{address, amount}] or CSV `address,amount` rows
+    #[arg(long)]
+    allocations: PathBuf,
+    /// Operator tree file, read if present and rewritten after the update
+    #[arg(long)]
+    tree: PathBuf,
+  },
+  /// Grow the campaign to hold more participants
+  Extend {
+    #[command(flatten)]
+    campaign: CampaignArgs,
+    #[arg(long)]
+    size: u64,
+  },
+  /// Claim the keypair's allocation using the tree file
+  Claim {
+    #[command(flatten)]
+    campaign: CampaignArgs,
+    #[arg(long)]
+    tree: PathBuf,
+    /// Raw token amount, the whole unclaimed allocation if omitted
+    #[arg(long)]
+    amount: Option<u64>,
+    /// Wallet receiving the tokens instead of the claimer
+    #[arg(long)]
+    recipient: Option<Pubkey>,
+  },
+  /// Print the claim parameters and proof for an address
+  Proof {
+    #[arg(long)]
+    tree: PathBuf,
+    #[arg(long)]
+    address: Pubkey,
+  },
+}
+
+fn read_keypair(path: &str) -> Result<Keypair> {
+  let path = match path.strip_prefix("~/") {
+    Some(rest) => format!("{}/{}", std::env::var("HOME").unwrap_or_default(), rest),
+    None => path.to_string(),
+  };
+  read_keypair_file(&path).map_err(|err| anyhow!("reading keypair {path}: {err}"))
+}
+
+fn send(client: &RpcClient, payer: &Keypair, instructions: &[Instruction]) -> Result<()> {
+  let blockhash = client.get_latest_blockhash()?;
+  let tx = Transaction::new_signed_with_payer(instructions, Some(&payer.pubkey()), &[payer], blockhash);
+  let signature = client.send_and_confirm_transaction(&tx).context("sending transaction")?;
+  println!("signature: {signature}");
+  Ok(())
+}
+
+fn fetch_campaign(client: &RpcClient, campaign: &CampaignRef) -> Result<CampaignState> {
+  let data = client.get_account_data(&campaign.address()).context("fetching campaign")?;
+  Ok(decode_campaign(&data)?)
+}
+
+fn print_campaign(address: &Pubkey, state: &CampaignState) {
+  println!("campaign:            {address}");
+  println!("launcher:            {}", state.launcher);
+  println!("mint:                {}", state.mint);
+  println!("distributor:         {}", state.distributor);
+  println!("seed:                {}", state.seed);
+  println!("fund:                {}", state.fund);
+  println!("start_ts:            {}", state.start_ts);
+  println!("end_ts:              {}", state.end_ts);
+  println!("claim window:        {} .. {}", state.claim_open_ts, state.claim_close_ts);
+  println!("vesting:             cliff {} / duration {}", state.vesting_cliff, state.vesting_duration);
+  println!("vesting_outstanding: {}", state.vesting_outstanding);
+  println!("locked:              {}", state.locked == 1);
+  println!("closed:              {}", state.closed == 1);
+  println!("hash:                algo {} / version {}", state.hash_algo, state.hash_version);
+  println!("merkle_root:         {}", to_hex(&state.merkle_root));
+  println!("root_grace_slots:    {}", state.root_grace_slots);
+  for entry in state.root_history.iter().filter(|entry| entry.root != [0u8; 32]) {
+    println!("  replaced root:     {} at slot {}", to_hex(&entry.root), entry.slot);
+  }
+  println!("participants:        {}", state.rewards.len());
+  println!("unallocated:         {}", state.rewards.first().copied().unwrap_or_default());
+  for (index, reward) in state.rewards.iter().enumerate().skip(1).filter(|(_, reward)| **reward > 0) {
+    println!("  [{index}] {reward} ({} claimed)", state.claimed.get(index).copied().unwrap_or_default());
+  }
+}
+
+fn main() -> Result<()> {
+  let cli = Cli::parse();
+  let client = RpcClient::new_with_commitment(cli.url.clone(), CommitmentConfig::confirmed());
+
+  match cli.command {
+    Command::Launch {
+      mint, seed, fund, distributor, end_ts, start_ts, claim_open_ts, claim_close_ts,
+      vesting_cliff, vesting_duration, root_grace_slots, hash_algo, fee_mint, token_program,
+    } => {
+      let payer = read_keypair(&cli.keypair)?;
+      let mut campaign = CampaignRef::new(payer.pubkey(), mint, seed);
+      if let Some(token_program) = token_program {
+        campaign = campaign.with_token_program(token_program);
+      }
+      let config = decode_config(&client.get_account_data(&config_address().0).context("fetching config")?)?;
+      let fee_config = decode_fee_config(&client.get_account_data(&fee_config_address().0).context("fetching fee config")?)?;
+      let schedule = CampaignSchedule {
+        start_ts,
+        end_ts,
+        claim_open_ts,
+        claim_close_ts,
+        vesting_cliff,
+        vesting_duration,
+        root_grace_slots,
+      };
+      let hash_algo = match hash_algo {
+        HashAlgo::Sha256 => HASH_ALGO_SHA256,
+        HashAlgo::Keccak256 => HASH_ALGO_KECCAK256,
+      };
+      let mut params = LaunchParams::new(fund, schedule, hash_algo);
+      if let Some(fee_mint) = fee_mint {
+        params = params.fee_mint(fee_mint);
+      }
+      send(&client, &payer, &[campaign.launch(&distributor, &config, &fee_config, params)])?;
+      println!("campaign: {}", campaign.address());
+    }
+    Command::Quote { launcher, fee_mint } => {
+      let payer = read_keypair(&cli.keypair)?;
+      let config = decode_config(&client.get_account_data(&config_address().0).context("fetching config")?)?;
+      let ix = quote_fee(&config, &launcher.unwrap_or(payer.pubkey()), fee_mint);
+      let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], client.get_latest_blockhash()?);
+      let result = client.simulate_transaction(&tx).context("simulating quote")?.value;
+      if let Some(err) = result.err {
+        bail!("quote failed: {err}");
+      }
+      let return_data = result.return_data.ok_or_else(|| anyhow!("quote returned no data"))?;
+      let data = base64::engine::general_purpose::STANDARD.decode(&return_data.data.0)?;
+      let fee = decode_fee_quote(&data).ok_or_else(|| anyhow!("malformed quote"))?;
+      match fee_mint {
+        Some(fee_mint) => println!("service fee: {fee} base units of {fee_mint}"),
+        None => println!("service fee: {fee} lamports"),
+      }
+    }
+    Command::Show { campaign } => {
+      let campaign = campaign.campaign();
+      print_campaign(&campaign.address(), &fetch_campaign(&client, &campaign)?);
+    }
+    Command::Lock { campaign } => {
+      let payer = read_keypair(&cli.keypair)?;
+      send(&client, &payer, &[campaign.campaign().lock(&payer.pubkey())])?;
+    }
+    Command::Update { campaign, allocations, tree } => {
+      let payer = read_keypair(&cli.keypair)?;
+      let campaign = campaign.campaign();
+      let state = fetch_campaign(&client, &campaign)?;
+      let previous = if tree.exists() { Some(TreeFile::load(&tree)?) } else { None };
+      let (tree_file, participants) = apply_allocations(&state, previous, &read_allocations(&allocations)?)?;
+      let root = tree_file.tree()?.root();
+      send(&client, &payer, &[campaign.update(&payer.pubkey(), root, participants)])?;
+      tree_file.save(&tree)?;
+      println!("root: {}", tree_file.root);
+    }
+    Command::Extend { campaign, size } => {
+      let payer = read_keypair(&cli.keypair)?;
+      send(&client, &payer, &[campaign.campaign().extend(&payer.pubkey(), size)])?;
+    }
+    Command::Claim { campaign, tree, amount, recipient } => {
+      let payer = read_keypair(&cli.keypair)?;
+      let campaign = campaign.campaign();
+      let tree_file = TreeFile::load(&tree)?;
+      let leaf = tree_file.find(&payer.pubkey()).ok_or_else(|| anyhow!("{} has no allocation", payer.pubkey()))?;
+      let mut params = ClaimParams::from_tree(&tree_file.tree()?, leaf.index)
+        .ok_or_else(|| anyhow!("index {} is outside the tree", leaf.index))?;
+      if let Some(amount) = amount {
+        params = params.amount(amount);
+      }
+      if let Some(recipient) = recipient {
+        params = params.recipient(recipient);
+      }
+      if fetch_campaign(&client, &campaign)?.is_vesting() {
+        if params.recipient.is_some() {
+          bail!("vesting campaigns cannot pay out to another recipient");
+        }
+        params = params.vesting();
+      }
+      send(&client, &payer, &[campaign.claim(&payer.pubkey(), params)])?;
+    }
+    Command::Proof { tree, address } => {
+      let tree_file = TreeFile::load(&tree)?;
+      let leaf = tree_file.find(&address).ok_or_else(|| anyhow!("{address} has no allocation"))?;
+      let proof = tree_file.tree()?
+        .proof(leaf.index as usize)
+        .ok_or_else(|| anyhow!("index {} is outside the tree", leaf.index))?;
+      let output = serde_json::json!({
+        "address": address.to_string(),
+        "user_idx": leaf.index,
+        "amount": leaf.amount,
+        "nonce": leaf.nonce,
+        "root": tree_file.root,
+        "proof": proof.iter().map(|node| to_hex(node)).collect::<Vec<_>>(),
+      });
+      println!("{}", serde_json::to_string_pretty(&output)?);
+    }
+  }
+  Ok(())
+}
@@ -0,0 +1,20 @@
+use anchor_lang::{AccountDeserialize, Result};
+use commi_merkle::state::{CampaignState, Config, FeeConfig, VestingState};
+
+// Each decoder checks the account discriminator before deserializing
+
+pub fn decode_campaign(data: &[u8]) -> Result<CampaignState> {
+  CampaignState::try_deserialize(&mut &data[..])
+}
+
+pub fn decode_config(data: &[u8]) -> Result<Config> {
+  Config::try_deserialize(&mut &data[..])
+}
+
+pub fn decode_fee_config(data: &[u8]) -> Result<FeeConfig> {
+  FeeConfig::try_deserialize(&mut &data[..])
+}
+
+pub fn decode_vesting(data: &[u8]) -> Result<VestingState> {
+  VestingState::try_deserialize(&mut &data[..])
+}
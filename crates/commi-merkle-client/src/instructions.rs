@@ -0,0 +1,225 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use anchor_spl::{associated_token, token};
+use commi_merkle::state::{CampaignSchedule, Config, FeeConfig};
+use commi_merkle_tree::MerkleTree;
+use crate::pda::{associated_token_address, campaign_address, config_address, fee_config_address, vault_address, vesting_address};
+
+fn instruction(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+  Instruction {
+    program_id: commi_merkle::ID,
+    accounts: accounts.to_account_metas(None),
+    data: data.data(),
+  }
+}
+
+// Passed whenever configured; the program only reads it when the primary feed is stale
+fn fallback_feed(config: &Config) -> Option<Pubkey> {
+  (config.fallback_feed != Pubkey::default()).then_some(config.fallback_feed)
+}
+
+// Read-only fee quote for `launcher`, in lamports or in `fee_mint` base units;
+// simulate it and pass the return data to `decode_fee_quote`
+pub fn quote_fee(config: &Config, launcher: &Pubkey, fee_mint: Option<Pubkey>) -> Instruction {
+  instruction(
+    commi_merkle::accounts::QuoteFee {
+      config: config_address().0,
+      fee_config: fee_config_address().0,
+      price_update: config.price_feed,
+      fallback_price_update: fallback_feed(config),
+      fee_mint,
+    },
+    commi_merkle::instruction::QuoteFee { launcher: *launcher },
+  )
+}
+
+pub fn decode_fee_quote(return_data: &[u8]) -> Option<u64> {
+  Some(u64::from_le_bytes(return_data.try_into().ok()?))
+}
+
+// Identifies a campaign by its PDA seeds and builds the instructions that target it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CampaignRef {
+  pub launcher: Pubkey,
+  pub mint: Pubkey,
+  pub seed: u64,
+  pub token_program: Pubkey,
+}
+
+impl CampaignRef {
+  pub fn new(launcher: Pubkey, mint: Pubkey, seed: u64) -> Self {
+    Self { launcher, mint, seed, token_program: token::ID }
+  }
+
+  // Token-2022 mints need their own token program for every ATA derivation
+  pub fn with_token_program(mut self, token_program: Pubkey) -> Self {
+    self.token_program = token_program;
+    self
+  }
+
+  pub fn address(&self) -> Pubkey {
+    campaign_address(&self.launcher, &self.mint, self.seed).0
+  }
+
+  pub fn vault(&self) -> Pubkey {
+    vault_address(&self.address(), &self.mint, &self.token_program)
+  }
+
+  pub fn launch(&self, distributor: &Pubkey, config: &Config, fee_config: &FeeConfig, params: LaunchParams) -> Instruction {
+    // Fee mints share the campaign's token program
+    let fee_ata = |owner: &Pubkey| params.fee_mint.map(|fee_mint| associated_token_address(owner, &fee_mint, &self.token_program));
+    instruction(
+      commi_merkle::accounts::Launch {
+        launcher: self.launcher,
+        distributor: *distributor,
+        fee_receiver: fee_config.fee_receiver,
+        config: config_address().0,
+        fee_config: fee_config_address().0,
+        campaign: self.address(),
+        mint: self.mint,
+        launcher_ata: associated_token_address(&self.launcher, &self.mint, &self.token_program),
+        vault: self.vault(),
+        price_update: config.price_feed,
+        fallback_price_update: fallback_feed(config),
+        fee_mint: params.fee_mint,
+        launcher_fee_ata: fee_ata(&self.launcher),
+        fee_receiver_ata: fee_ata(&fee_config.fee_receiver),
+        associated_token_program: associated_token::ID,
+        token_program: self.token_program,
+        system_program: system_program::ID,
+      },
+      commi_merkle::instruction::Launch {
+        seed: self.seed,
+        fund: params.fund,
+        schedule: params.schedule,
+        hash_algo: params.hash_algo,
+      },
+    )
+  }
+
+  fn update_accounts(&self, distributor: &Pubkey) -> commi_merkle::accounts::Update {
+    commi_merkle::accounts::Update {
+      distributor: *distributor,
+      config: config_address().0,
+      launcher: self.launcher,
+      campaign: self.address(),
+      mint: self.mint,
+      system_program: system_program::ID,
+    }
+  }
+
+  // Each participant is [user_idx, amount added to that index]
+  pub fn update(&self, distributor: &Pubkey, root: [u8; 32], participants: Vec<[u64; 2]>) -> Instruction {
+    instruction(
+      self.update_accounts(distributor),
+      commi_merkle::instruction::Update { root, participants },
+    )
+  }
+
+  pub fn lock(&self, distributor: &Pubkey) -> Instruction {
+    instruction(self.update_accounts(distributor), commi_merkle::instruction::Lock {})
+  }
+
+  pub fn extend(&self, distributor: &Pubkey, new_participants: u64) -> Instruction {
+    instruction(
+      commi_merkle::accounts::Extend {
+        distributor: *distributor,
+        config: config_address().0,
+        campaign: self.address(),
+        system_program: system_program::ID,
+      },
+      commi_merkle::instruction::Extend { new_participants },
+    )
+  }
+
+  pub fn claim(&self, claimer: &Pubkey, params: ClaimParams) -> Instruction {
+    let campaign = self.address();
+    let (claimer_ata, recipient_ata) = match params.recipient {
+      Some(recipient) => (None, Some(associated_token_address(&recipient, &self.mint, &self.token_program))),
+      None => (Some(associated_token_address(claimer, &self.mint, &self.token_program)), None),
+    };
+    instruction(
+      commi_merkle::accounts::Claim {
+        claimer: *claimer,
+        launcher: self.launcher,
+        config: config_address().0,
+        campaign,
+        mint: self.mint,
+        vault: self.vault(),
+        claimer_ata,
+        recipient: params.recipient,
+        recipient_ata,
+        vesting: params.vesting.then(|| vesting_address(&campaign, claimer).0),
+        associated_token_program: associated_token::ID,
+        token_program: self.token_program,
+        system_program: system_program::ID,
+      },
+      commi_merkle::instruction::Claim {
+        user_idx: params.user_idx,
+        proof: params.proof,
+        nonce: params.nonce,
+        claim_amount: params.claim_amount,
+      },
+    )
+  }
+}
+
+#[derive(Clone)]
+pub struct LaunchParams {
+  pub fund: u64,
+  pub schedule: CampaignSchedule,
+  pub hash_algo: u8,
+  pub fee_mint: Option<Pubkey>,
+}
+
+impl LaunchParams {
+  // Pays the service fee in SOL by default
+  pub fn new(fund: u64, schedule: CampaignSchedule, hash_algo: u8) -> Self {
+    Self { fund, schedule, hash_algo, fee_mint: None }
+  }
+
+  // Pays the service fee in a stablecoin whitelisted in the fee config
+  pub fn fee_mint(mut self, fee_mint: Pubkey) -> Self {
+    self.fee_mint = Some(fee_mint);
+    self
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClaimParams {
+  pub user_idx: u64,
+  pub proof: Vec<[u8; 32]>,
+  pub nonce: u64,
+  pub claim_amount: u64,
+  pub recipient: Option<Pubkey>,
+  pub vesting: bool,
+}
+
+impl ClaimParams {
+  // Claims the whole unclaimed allocation by default
+  pub fn new(user_idx: u64, proof: Vec<[u8; 32]>, nonce: u64) -> Self {
+    Self { user_idx, proof, nonce, claim_amount: u64::MAX, recipient: None, vesting: false }
+  }
+
+  pub fn from_tree(tree: &MerkleTree, user_idx: u64) -> Option<Self> {
+    let leaf = tree.leaf(user_idx as usize)?;
+    Some(Self::new(user_idx, tree.proof(user_idx as usize)?, leaf.nonce))
+  }
+
+  pub fn amount(mut self, claim_amount: u64) -> Self {
+    self.claim_amount = claim_amount;
+    self
+  }
+
+  pub fn recipient(mut self, recipient: Pubkey) -> Self {
+    self.recipient = Some(recipient);
+    self
+  }
+
+  // Vesting campaigns record the claim in a per-claimer vesting account
+  pub fn vesting(mut self) -> Self {
+    self.vesting = true;
+    self
+  }
+}
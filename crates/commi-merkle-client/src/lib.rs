@@ -0,0 +1,10 @@
+// Client helpers for commi-merkle: PDA/ATA derivation, instruction builders and
+// account decoders, so integrators never copy seeds or layouts by hand.
+
+pub mod accounts;
+pub mod instructions;
+pub mod pda;
+
+pub use commi_merkle::state::{CampaignSchedule, CampaignState, Config, FeeConfig, VestingState};
+pub use commi_merkle::ID as PROGRAM_ID;
+pub use commi_merkle_tree as tree;
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::{pubkey, Pubkey};
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const FEE_CONFIG_SEED: &[u8] = b"fee_config";
+pub const CAMPAIGN_SEED: &[u8] = b"campaign";
+pub const VESTING_SEED: &[u8] = b"vesting";
+
+const BPF_LOADER_UPGRADEABLE: Pubkey = pubkey!("BPFLoaderUpgradeab1e11111111111111111111111");
+
+pub fn config_address() -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[CONFIG_SEED], &commi_merkle::ID)
+}
+
+pub fn fee_config_address() -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[FEE_CONFIG_SEED], &commi_merkle::ID)
+}
+
+// [b"campaign", launcher, mint, seed LE]
+pub fn campaign_address(launcher: &Pubkey, mint: &Pubkey, seed: u64) -> (Pubkey, u8) {
+  Pubkey::find_program_address(
+    &[CAMPAIGN_SEED, launcher.as_ref(), mint.as_ref(), seed.to_le_bytes().as_ref()],
+    &commi_merkle::ID,
+  )
+}
+
+pub fn vesting_address(campaign: &Pubkey, claimer: &Pubkey) -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[VESTING_SEED, campaign.as_ref(), claimer.as_ref()], &commi_merkle::ID)
+}
+
+// Used by `initialize_config` to check the upgrade authority
+pub fn program_data_address() -> Pubkey {
+  Pubkey::find_program_address(&[commi_merkle::ID.as_ref()], &BPF_LOADER_UPGRADEABLE).0
+}
+
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+  get_associated_token_address_with_program_id(owner, mint, token_program)
+}
+
+// The campaign's token vault is the campaign PDA's associated token account
+pub fn vault_address(campaign: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+  associated_token_address(campaign, mint, token_program)
+}
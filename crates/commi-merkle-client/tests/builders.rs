@@ -0,0 +1,82 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountSerialize, Discriminator};
+use commi_merkle_client::accounts::decode_campaign;
+use commi_merkle_client::instructions::{CampaignRef, ClaimParams};
+use commi_merkle_client::pda::campaign_address;
+use commi_merkle_client::tree::{Leaf, LeafHasher, MerkleTree};
+use commi_merkle_client::{CampaignState, PROGRAM_ID};
+
+fn campaign() -> CampaignRef {
+  CampaignRef::new(Pubkey::new_unique(), Pubkey::new_unique(), 42)
+}
+
+#[test]
+fn campaign_address_includes_seed() {
+  let campaign = campaign();
+  let (address, _) = Pubkey::find_program_address(
+    &[b"campaign", campaign.launcher.as_ref(), campaign.mint.as_ref(), &42u64.to_le_bytes()],
+    &PROGRAM_ID,
+  );
+  assert_eq!(campaign.address(), address);
+  assert_ne!(campaign_address(&campaign.launcher, &campaign.mint, 43).0, address);
+}
+
+#[test]
+fn claim_from_tree_targets_claimer_ata() {
+  let campaign = campaign();
+  let claimer = Pubkey::new_unique();
+  let tree = MerkleTree::with_size(
+    LeafHasher::default(),
+    vec![Leaf::new(claimer.to_bytes(), 500, 3, 9)],
+    32,
+  );
+  let params = ClaimParams::from_tree(&tree, 3).unwrap();
+  assert_eq!(params.proof.len(), 5);
+  let ix = campaign.claim(&claimer, params);
+
+  assert_eq!(ix.program_id, PROGRAM_ID);
+  assert_eq!(ix.data[0], 2);
+  assert_eq!(ix.accounts[0].pubkey, claimer);
+  assert_eq!(ix.accounts[3].pubkey, campaign.address());
+  // Unused optional accounts are passed as the program id
+  assert_eq!(ix.accounts[7].pubkey, PROGRAM_ID);
+  assert_eq!(ix.accounts[9].pubkey, PROGRAM_ID);
+}
+
+#[test]
+fn decode_campaign_round_trip() {
+  let state = CampaignState {
+    launcher: Pubkey::new_unique(),
+    mint: Pubkey::new_unique(),
+    distributor: Pubkey::new_unique(),
+    fund: 10_000,
+    seed: 42,
+    start_ts: 0,
+    end_ts: 3600,
+    claim_open_ts: 0,
+    claim_close_ts: 0,
+    vesting_cliff: 0,
+    vesting_duration: 0,
+    vesting_outstanding: 0,
+    realloc_rent: 0,
+    locked: 0,
+    closed: 0,
+    hash_version: 1,
+    hash_algo: 0,
+    merkle_root: [7u8; 32],
+    root_grace_slots: 150,
+    root_history_idx: 0,
+    root_history: Default::default(),
+    rewards: vec![10_000, 500, 0],
+    claimed: vec![0, 200, 0],
+  };
+  let mut data = vec![];
+  state.try_serialize(&mut data).unwrap();
+  assert_eq!(&data[..1], CampaignState::DISCRIMINATOR);
+
+  let decoded = decode_campaign(&data).unwrap();
+  assert_eq!(decoded.seed, 42);
+  assert_eq!(decoded.merkle_root, [7u8; 32]);
+  assert_eq!(decoded.rewards, vec![10_000, 500, 0]);
+  assert_eq!(decoded.outstanding(1), 300);
+}
@@ -0,0 +1,73 @@
+pub type Hash = [u8; 32];
+
+// Legacy trees hash leaves and nodes without a prefix; newer campaigns domain-separate them
+pub const HASH_VERSION_LEGACY: u8 = 0;
+pub const HASH_VERSION_PREFIXED: u8 = 1;
+
+pub const LEAF_PREFIX: u8 = 0x00;
+pub const NODE_PREFIX: u8 = 0x01;
+
+// Keccak-256 only swaps the hash function; the encoding is not the one common EVM merkle libraries
+// produce (abi-encoded leaves, sorted pairs). EVM tooling must build prefixed trees as:
+//   leaf = keccak256(0x00 || claimer (32 bytes) || amount u64 LE || index u64 LE || nonce u64 LE)
+//   node = keccak256(0x01 || left || right), left being the child with the lower index
+// Pairs are never sorted, the leaf index decides each side along the proof.
+pub const HASH_ALGO_SHA256: u8 = 0;
+pub const HASH_ALGO_KECCAK256: u8 = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LeafHasher {
+  pub hash_algo: u8,
+  pub hash_version: u8,
+}
+
+impl Default for LeafHasher {
+  fn default() -> Self {
+    Self::new(HASH_ALGO_SHA256, HASH_VERSION_PREFIXED)
+  }
+}
+
+impl LeafHasher {
+  pub const fn new(hash_algo: u8, hash_version: u8) -> Self {
+    Self { hash_algo, hash_version }
+  }
+
+  pub fn hashv(&self, data: &[&[u8]]) -> Hash {
+    if self.hash_algo == HASH_ALGO_KECCAK256 {
+      solana_nostd_keccak::hashv(data)
+    } else {
+      solana_nostd_sha256::hashv(data)
+    }
+  }
+
+  // claimer || amount LE || index LE || nonce LE
+  pub fn leaf(&self, claimer: &[u8; 32], amount: u64, index: u64, nonce: u64) -> Hash {
+    let prefix: &[u8] = if self.hash_version == HASH_VERSION_LEGACY { &[] } else { &[LEAF_PREFIX] };
+    self.hashv(&[
+      prefix,
+      claimer.as_ref(),
+      amount.to_le_bytes().as_ref(),
+      index.to_le_bytes().as_ref(),
+      nonce.to_le_bytes().as_ref(),
+    ])
+  }
+
+  pub fn node(&self, left: &Hash, right: &Hash) -> Hash {
+    let prefix: &[u8] = if self.hash_version == HASH_VERSION_LEGACY { &[] } else { &[NODE_PREFIX] };
+    self.hashv(&[prefix, left.as_ref(), right.as_ref()])
+  }
+
+  // Walks the proof from the leaf up; the index bits pick the side at each level
+  pub fn root_from_proof(&self, leaf: Hash, index: u64, proof: &[Hash]) -> Hash {
+    let mut node = leaf;
+    for (i, sibling) in proof.iter().enumerate() {
+      let position = index >> i;
+      if position % 2 == 0 {
+        node = self.node(&node, sibling);
+      } else {
+        node = self.node(sibling, &node);
+      }
+    }
+    node
+  }
+}
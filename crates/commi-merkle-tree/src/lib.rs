@@ -0,0 +1,8 @@
+// Off-chain merkle tree for commi-merkle campaigns. The `leaf` module is also
+// used by the program itself, so proofs built here verify on-chain as is.
+
+pub mod leaf;
+pub mod tree;
+
+pub use leaf::*;
+pub use tree::*;
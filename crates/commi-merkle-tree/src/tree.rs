@@ -0,0 +1,130 @@
+use crate::leaf::{Hash, LeafHasher};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Leaf {
+  pub claimer: [u8; 32],
+  pub amount: u64,
+  pub index: u64,
+  pub nonce: u64,
+}
+
+impl Leaf {
+  pub fn new(claimer: [u8; 32], amount: u64, index: u64, nonce: u64) -> Self {
+    Self { claimer, amount, index, nonce }
+  }
+
+  // Unallocated slots hold the default claimer with a zero amount, which can never be claimed
+  pub fn empty(index: u64) -> Self {
+    Self { index, ..Self::default() }
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct MerkleTree {
+  hasher: LeafHasher,
+  leaves: Vec<Leaf>,
+  layers: Vec<Vec<Hash>>,
+}
+
+impl MerkleTree {
+  // Leaves are placed at their own index and the tree is padded to a power of two;
+  // a later leaf with the same index replaces an earlier one
+  pub fn new(hasher: LeafHasher, leaves: Vec<Leaf>) -> Self {
+    Self::with_size(hasher, leaves, 1)
+  }
+
+  // Same as `new`, but never smaller than `size` leaves (campaigns start with 32 slots)
+  pub fn with_size(hasher: LeafHasher, leaves: Vec<Leaf>, size: usize) -> Self {
+    let needed = leaves.iter().map(|leaf| leaf.index as usize + 1).max().unwrap_or(0);
+    let width = needed.max(size).max(1).next_power_of_two();
+    let mut slots: Vec<Leaf> = (0..width as u64).map(Leaf::empty).collect();
+    for leaf in leaves {
+      let index = leaf.index as usize;
+      slots[index] = leaf;
+    }
+    let mut tree = Self { hasher, leaves: slots, layers: vec![] };
+    tree.build();
+    tree
+  }
+
+  fn build(&mut self) {
+    let mut level: Vec<Hash> = self.leaves
+      .iter()
+      .map(|leaf| self.hasher.leaf(&leaf.claimer, leaf.amount, leaf.index, leaf.nonce))
+      .collect();
+    self.layers = vec![level.clone()];
+    while level.len() > 1 {
+      level = level
+        .chunks(2)
+        .map(|pair| self.hasher.node(&pair[0], &pair[1]))
+        .collect();
+      self.layers.push(level.clone());
+    }
+  }
+
+  pub fn hasher(&self) -> LeafHasher {
+    self.hasher
+  }
+
+  pub fn root(&self) -> Hash {
+    self.layers[self.layers.len() - 1][0]
+  }
+
+  pub fn leaves(&self) -> &[Leaf] {
+    &self.leaves
+  }
+
+  pub fn leaf(&self, index: usize) -> Option<&Leaf> {
+    self.leaves.get(index)
+  }
+
+  pub fn find(&self, claimer: &[u8; 32]) -> Option<&Leaf> {
+    self.leaves.iter().find(|leaf| leaf.claimer == *claimer && leaf.amount > 0)
+  }
+
+  pub fn len(&self) -> usize {
+    self.leaves.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.leaves.is_empty()
+  }
+
+  pub fn proof(&self, index: usize) -> Option<Vec<Hash>> {
+    if index >= self.leaves.len() {
+      return None;
+    }
+    let proof = self.layers[..self.layers.len() - 1]
+      .iter()
+      .enumerate()
+      .map(|(level, nodes)| nodes[(index >> level) ^ 1])
+      .collect();
+    Some(proof)
+  }
+
+  // Replaces a leaf and rehashes only its path to the root
+  pub fn set_leaf(&mut self, leaf: Leaf) -> Option<()> {
+    let mut index = leaf.index as usize;
+    if index >= self.leaves.len() {
+      return None;
+    }
+    self.layers[0][index] = self.hasher.leaf(&leaf.claimer, leaf.amount, leaf.index, leaf.nonce);
+    self.leaves[index] = leaf;
+    for level in 1..self.layers.len() {
+      index /= 2;
+      let left = self.layers[level - 1][index * 2];
+      let right = self.layers[level - 1][index * 2 + 1];
+      self.layers[level][index] = self.hasher.node(&left, &right);
+    }
+    Some(())
+  }
+
+  pub fn verify(&self, leaf: &Leaf, proof: &[Hash]) -> bool {
+    verify_proof(&self.hasher, &self.root(), leaf, proof)
+  }
+}
+
+pub fn verify_proof(hasher: &LeafHasher, root: &Hash, leaf: &Leaf, proof: &[Hash]) -> bool {
+  let hash = hasher.leaf(&leaf.claimer, leaf.amount, leaf.index, leaf.nonce);
+  hasher.root_from_proof(hash, leaf.index, proof) == *root
+}
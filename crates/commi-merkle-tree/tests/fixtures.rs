@@ -0,0 +1,102 @@
+// Cross-checks the tree against fixtures generated independently of this crate.
+// Leaf 0 belongs to the distributor keypair the TS suite loads from test-distributor.json.
+
+use commi_merkle_tree::{verify_proof, Hash, Leaf, LeafHasher, MerkleTree};
+use serde_json::Value;
+
+fn read_json(path: &str) -> Value {
+  let path = format!("{}/{}", env!("CARGO_MANIFEST_DIR"), path);
+  serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap()
+}
+
+fn decode_hex(value: &Value) -> Hash {
+  let text = value.as_str().unwrap();
+  let mut out = [0u8; 32];
+  for (i, byte) in out.iter_mut().enumerate() {
+    *byte = u8::from_str_radix(&text[i * 2..i * 2 + 2], 16).unwrap();
+  }
+  out
+}
+
+fn fixture_leaves(fixture: &Value) -> Vec<Leaf> {
+  fixture["leaves"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .map(|leaf| Leaf::new(
+      decode_hex(&leaf["claimer"]),
+      leaf["amount"].as_str().unwrap().parse().unwrap(),
+      leaf["index"].as_u64().unwrap(),
+      leaf["nonce"].as_str().unwrap().parse().unwrap(),
+    ))
+    .collect()
+}
+
+fn fixture_trees(fixture: &Value) -> Vec<(LeafHasher, &Value)> {
+  fixture["trees"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .map(|tree| {
+      let hasher = LeafHasher::new(
+        tree["hash_algo"].as_u64().unwrap() as u8,
+        tree["hash_version"].as_u64().unwrap() as u8,
+      );
+      (hasher, tree)
+    })
+    .collect()
+}
+
+#[test]
+fn fixture_uses_test_distributor_key() {
+  let fixture = read_json("tests/fixtures/distributor-tree.json");
+  let keypair: Vec<u8> = serde_json::from_value(read_json("../../test-distributor.json")).unwrap();
+  assert_eq!(fixture_leaves(&fixture)[0].claimer.as_ref(), &keypair[32..]);
+}
+
+#[test]
+fn roots_and_leaf_hashes_match_fixtures() {
+  let fixture = read_json("tests/fixtures/distributor-tree.json");
+  let width = fixture["width"].as_u64().unwrap() as usize;
+  for (hasher, expected) in fixture_trees(&fixture) {
+    let tree = MerkleTree::new(hasher, fixture_leaves(&fixture));
+    assert_eq!(tree.len(), width);
+    assert_eq!(tree.root(), decode_hex(&expected["root"]), "{hasher:?}");
+    for (leaf, hash) in tree.leaves().iter().zip(expected["leaf_hashes"].as_array().unwrap()) {
+      assert_eq!(hasher.leaf(&leaf.claimer, leaf.amount, leaf.index, leaf.nonce), decode_hex(hash));
+    }
+  }
+}
+
+#[test]
+fn proofs_match_fixtures_and_verify() {
+  let fixture = read_json("tests/fixtures/distributor-tree.json");
+  for (hasher, expected) in fixture_trees(&fixture) {
+    let tree = MerkleTree::new(hasher, fixture_leaves(&fixture));
+    for leaf in fixture_leaves(&fixture) {
+      let proof = tree.proof(leaf.index as usize).unwrap();
+      let expected_proof: Vec<Hash> = expected["proofs"][leaf.index.to_string()]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(decode_hex)
+        .collect();
+      assert_eq!(proof, expected_proof);
+      assert!(tree.verify(&leaf, &proof));
+
+      let tampered = Leaf { amount: leaf.amount.wrapping_add(1), ..leaf.clone() };
+      assert!(!verify_proof(&hasher, &tree.root(), &tampered, &proof));
+    }
+  }
+}
+
+#[test]
+fn set_leaf_matches_rebuild() {
+  let fixture = read_json("tests/fixtures/distributor-tree.json");
+  let mut leaves = fixture_leaves(&fixture);
+  let mut tree = MerkleTree::new(LeafHasher::default(), leaves.clone());
+  let updated = Leaf::new([9u8; 32], 77, 6, 1);
+  tree.set_leaf(updated.clone()).unwrap();
+  leaves.push(updated);
+  assert_eq!(tree.root(), MerkleTree::new(LeafHasher::default(), leaves).root());
+}
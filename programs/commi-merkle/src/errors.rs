@@ -43,4 +43,121 @@ pub enum CommiError {
   #[msg("Invaid Launcher")]
   InvalidLauncher,
 
+  // Config Error
+  #[msg("Invalid Admin")]
+  InvalidAdmin,
+
+  #[msg("Invalid Fee Receiver")]
+  InvalidFeeReceiver,
+
+  #[msg("Invalid Pending Admin")]
+  InvalidPendingAdmin,
+
+  #[msg("Invalid Pending Distributor")]
+  InvalidPendingDistributor,
+
+  #[msg("Distributor Already Exists")]
+  DuplicateDistributor,
+
+  #[msg("Too Many Distributors")]
+  TooManyDistributors,
+
+  #[msg("Program Paused")]
+  ProgramPaused,
+
+  #[msg("Invalid Pause Flags")]
+  InvalidPauseFlags,
+
+  // Expiry Error
+  #[msg("Invalid Schedule")]
+  InvalidSchedule,
+
+  #[msg("Campaign Not Ended")]
+  CampaignNotEnded,
+
+  #[msg("Campaign Ended")]
+  CampaignEnded,
+
+  #[msg("Campaign Closed")]
+  CampaignClosed,
+
+  // Close Error
+  #[msg("Campaign Not Empty")]
+  CampaignNotEmpty,
+
+  // Withdraw Error
+  #[msg("Campaign Not Locked or Ended")]
+  CampaignNotLocked,
+
+  // Claim Window Error
+  #[msg("Claim Window Not Open")]
+  ClaimWindowNotOpen,
+
+  #[msg("Claim Window Closed")]
+  ClaimWindowClosed,
+
+  // Vesting Error
+  #[msg("Vesting Account Required")]
+  VestingAccountRequired,
+
+  #[msg("Nothing Vested")]
+  NothingVested,
+
+  // Relay Error
+  #[msg("Invalid Relay Signature")]
+  InvalidRelaySignature,
+
+  #[msg("Invalid Recipient")]
+  InvalidRecipient,
+
+  #[msg("Invalid Claim Accounts")]
+  InvalidClaimAccounts,
+
+  // Hash Error
+  #[msg("Invalid Hash Algorithm")]
+  InvalidHashAlgo,
+
+  // Fee Error
+  #[msg("Too Many Fee Waivers")]
+  TooManyFeeWaivers,
+
+  #[msg("Invalid Service Fee")]
+  InvalidServiceFee,
+
+  #[msg("Too Many Fee Mints")]
+  TooManyFeeMints,
+
+  #[msg("Invalid Fee Mint")]
+  InvalidFeeMint,
+
+  #[msg("Fee Token Accounts Required")]
+  FeeTokenAccountsRequired,
+
+  // Oracle Error
+  #[msg("Stale Price")]
+  StalePrice,
+
+  #[msg("Non Positive Price")]
+  NonPositivePrice,
+
+  #[msg("Price Confidence Too Wide")]
+  PriceConfidenceTooWide,
+
+  #[msg("Insufficient Price Verification")]
+  InsufficientPriceVerification,
+
+  #[msg("Invalid Oracle Policy")]
+  InvalidOraclePolicy,
+
+  #[msg("Price Not Trading")]
+  PriceNotTrading,
+
+  // Migration Error
+  #[msg("Not A Legacy Campaign")]
+  NotLegacyCampaign,
+
+  // Extend Error
+  #[msg("Cannot Shrink Campaign")]
+  CannotShrinkCampaign,
+
 }
\ No newline at end of file
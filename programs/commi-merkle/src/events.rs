@@ -5,8 +5,23 @@ use anchor_lang::prelude::*;
 pub struct LaunchEvent {
   pub fund: u64,
   pub seed: u64,
+  pub service_fee: u64,
+  // Price account the fee was priced from, default when the launcher is waived or paid in tokens
+  pub price_feed: Pubkey,
+  // Stablecoin the fee was paid in, default for SOL
+  pub fee_mint: Pubkey,
   pub launcher: Pubkey,
   pub mint: Pubkey,
+  pub distributor: Pubkey,
+  pub start_ts: i64,
+  pub end_ts: i64,
+  pub claim_open_ts: i64,
+  pub claim_close_ts: i64,
+  pub vesting_cliff: i64,
+  pub vesting_duration: i64,
+  pub root_grace_slots: u64,
+  pub hash_version: u8,
+  pub hash_algo: u8,
 }
 
 #[event]
@@ -19,11 +34,132 @@ pub struct UpdateEvent {
 pub struct ClaimEvent {
   pub claimer: Pubkey,
   pub campaign: Pubkey,
+  pub recipient: Pubkey,
   pub amount: u64,
+  pub remaining: u64,
+  pub vested: bool,
 }
 
 #[event]
 pub struct ExtendEvent {
   pub size: u64,
   pub campaign: Pubkey,
-}
\ No newline at end of file
+}
+
+#[event]
+pub struct ProposeAdminEvent {
+  pub admin: Pubkey,
+  pub pending_admin: Pubkey,
+}
+
+#[event]
+pub struct AcceptAdminEvent {
+  pub previous_admin: Pubkey,
+  pub admin: Pubkey,
+}
+
+#[event]
+pub struct ProposeDistributorEvent {
+  pub pending_distributor: Pubkey,
+}
+
+#[event]
+pub struct AcceptDistributorEvent {
+  pub distributor: Pubkey,
+}
+
+#[event]
+pub struct RemoveDistributorEvent {
+  pub distributor: Pubkey,
+}
+
+#[event]
+pub struct AssignDistributorEvent {
+  pub campaign: Pubkey,
+  pub previous_distributor: Pubkey,
+  pub distributor: Pubkey,
+}
+
+#[event]
+pub struct PauseEvent {
+  pub flags: u8,
+  pub paused: u8,
+}
+
+#[event]
+pub struct UnpauseEvent {
+  pub flags: u8,
+  pub paused: u8,
+}
+
+#[event]
+pub struct OraclePolicyEvent {
+  pub max_conf_bps: u16,
+  pub min_verification: u8,
+}
+
+#[event]
+pub struct PriceFeedEvent {
+  pub previous_price_feed: Pubkey,
+  pub price_feed: Pubkey,
+}
+
+#[event]
+pub struct FallbackFeedEvent {
+  pub fallback_feed: Pubkey,
+  pub fallback_kind: u8,
+}
+
+#[event]
+pub struct ClawbackEvent {
+  pub campaign: Pubkey,
+  pub launcher: Pubkey,
+  pub amount: u64,
+}
+
+#[event]
+pub struct CloseEvent {
+  pub campaign: Pubkey,
+  pub launcher: Pubkey,
+  pub distributor: Pubkey,
+  pub distributor_refund: u64,
+  // Tokens left in the vault and returned to the launcher
+  pub swept: u64,
+}
+
+#[event]
+pub struct TopUpEvent {
+  pub campaign: Pubkey,
+  pub amount: u64,
+  pub fund: u64,
+}
+
+#[event]
+pub struct WithdrawEvent {
+  pub campaign: Pubkey,
+  pub amount: u64,
+  pub fund: u64,
+}
+
+#[event]
+pub struct WithdrawVestedEvent {
+  pub claimer: Pubkey,
+  pub campaign: Pubkey,
+  pub amount: u64,
+  pub remaining: u64,
+}
+
+#[event]
+pub struct FeeConfigUpdated {
+  pub fee_receiver: Pubkey,
+  pub fee_usd_cents: u64,
+  pub waivers: Vec<Pubkey>,
+  pub fee_mints: Vec<Pubkey>,
+}
+
+#[event]
+pub struct MigrateEvent {
+  pub campaign: Pubkey,
+  pub distributor: Pubkey,
+  pub participants: u64,
+}
@@ -1,12 +1,10 @@
 use anchor_lang::prelude::*;
 use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token_interface::{TokenInterface, TokenAccount, Mint, transfer_checked, TransferChecked};
-use solana_nostd_sha256::hashv;
-use crate::state::CampaignState;
+use anchor_spl::token_interface::{TokenInterface, TokenAccount, Mint};
+use crate::state::{CampaignState, Config, VestingState, PAUSE_CLAIM};
 use crate::errors::CommiError;
 use crate::events::ClaimEvent;
 
-
 #[derive(Accounts)]
 pub struct Claim<'info> {
   #[account(mut)]
@@ -15,6 +13,12 @@ pub struct Claim<'info> {
   #[account(mut)]
   pub launcher: SystemAccount<'info>,
 
+  #[account(
+    seeds = [b"config"],
+    bump = config.bump,
+  )]
+  pub config: Account<'info, Config>,
+
   #[account(
     mut,
     seeds = [b"campaign", launcher.key().as_ref(), mint.key().as_ref(), campaign.seed.to_le_bytes().as_ref()],
@@ -40,7 +44,29 @@ pub struct Claim<'info> {
     associated_token::authority = claimer,
     associated_token::token_program = token_program
   )]
-  pub claimer_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+  pub claimer_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+  // Optional alternate wallet receiving the tokens; the leaf still commits to the claimer
+  pub recipient: Option<SystemAccount<'info>>,
+
+  #[account(
+    init_if_needed,
+    payer = claimer,
+    associated_token::mint = mint,
+    associated_token::authority = recipient,
+    associated_token::token_program = token_program
+  )]
+  pub recipient_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+  // Required when the campaign vests rewards instead of paying them out
+  #[account(
+    init_if_needed,
+    payer = claimer,
+    space = VestingState::space(),
+    seeds = [b"vesting", campaign.key().as_ref(), claimer.key().as_ref()],
+    bump,
+  )]
+  pub vesting: Option<Box<Account<'info, VestingState>>>,
 
   pub associated_token_program: Program<'info, AssociatedToken>,
   pub token_program: Interface<'info, TokenInterface>,
@@ -49,75 +75,57 @@ pub struct Claim<'info> {
 
 impl<'info> Claim<'info> {
 
-  fn verify_claim_status(&self, user_idx: u64, proof: Vec<[u8; 32]>, nonce: u64) -> Result<()> {
-    require_gt!(self.campaign.rewards.len(), user_idx as usize, CommiError::InvalidUserIdx);
-    require_gt!(self.campaign.rewards[user_idx as usize], 0, CommiError::InvalidClaimAmount);
-    let mut leaf = hashv(&[
-      self.claimer.key().to_bytes().as_ref(), 
-      self.campaign.rewards[user_idx as usize].to_le_bytes().as_ref(), 
-      user_idx.to_le_bytes().as_ref(),
-      nonce.to_le_bytes().as_ref()
-    ]);
-    for i in 0..proof.len() {
-      let position = user_idx >> i;
-      if position % 2 == 0 {
-        leaf = hashv(&[leaf.as_ref(), proof[i].as_ref()]);
-      } else {
-        leaf = hashv(&[proof[i].as_ref(), leaf.as_ref()]);
-      }
-    }
-    if leaf != self.campaign.merkle_root {
-      return err!(CommiError::InvalidProof);
-    }
-    return Ok(())
+  fn update_status(&mut self, user_idx: u64, amount: u64) -> Result<u64> {
+    Ok(self.campaign.take_reward(user_idx, amount))
   }
 
-  fn update_status(&mut self, user_idx: u64) -> Result<u64> {
-    let reward = self.campaign.rewards[user_idx as usize];
-    self.campaign.rewards[user_idx as usize] = 0;
-    Ok(reward)
+  fn vest_tokens(&mut self, amount: u64, bump: u8) -> Result<()> {
+    let claimer = self.claimer.key();
+    CampaignState::vest_tokens(&mut self.campaign, self.vesting.as_deref_mut(), claimer, amount, bump)
   }
 
-  fn claim_tokens(&self, user_idx: u64, bump: u8) -> Result<()> {
-    let launcher_key = self.launcher.key();
-    let mint_key = self.mint.key();
-    let seed = self.campaign.seed.to_le_bytes();
-    let signer_seeds: [&[&[u8]]; 1] = [&[
-        b"campaign",
-        launcher_key.as_ref(),
-        mint_key.as_ref(),
-        seed.as_ref(),
-        &[bump], // Use the bump from CampaignState
-    ]];
-
-    transfer_checked(
-      CpiContext::new_with_signer(
-        self.token_program.to_account_info(),
-        TransferChecked {
-          from: self.vault.to_account_info(),
-          mint: self.mint.to_account_info(),
-          to: self.claimer_ata.to_account_info(),
-          authority: self.campaign.to_account_info(),
-        },
-        &signer_seeds
-      ), 
-      self.campaign.rewards[user_idx as usize], 
-      self.mint.decimals
-    )?;
-    Ok(())
+  fn claim_tokens(&self, amount: u64, bump: u8) -> Result<()> {
+    let destination = match self.recipient {
+      Some(_) => self.recipient_ata.as_ref(),
+      None => self.claimer_ata.as_ref(),
+    }.ok_or(CommiError::InvalidRecipient)?;
+    CampaignState::transfer_from_vault(
+      &self.campaign,
+      bump,
+      self.vault.to_account_info(),
+      &self.mint,
+      destination.to_account_info(),
+      self.token_program.to_account_info(),
+      amount,
+    )
   }
 }
 
-pub fn handler(ctx: Context<Claim>, user_idx: u64, proof: Vec<[u8; 32]>, nonce: u64) -> Result<()> {
+pub fn handler(ctx: Context<Claim>, user_idx: u64, proof: Vec<[u8; 32]>, nonce: u64, claim_amount: u64) -> Result<()> {
+  require!(!ctx.accounts.config.is_paused(PAUSE_CLAIM), CommiError::ProgramPaused);
+  require_eq!(ctx.accounts.campaign.closed, 0, CommiError::CampaignClosed);
   require_eq!(ctx.accounts.campaign.locked, 0, CommiError::CampaignLocked);
-  ctx.accounts.verify_claim_status(user_idx, proof, nonce)?;
-  ctx.accounts.claim_tokens( user_idx, ctx.bumps.campaign)?;
-  let amount = ctx.accounts.update_status(user_idx)?;
+  let clock = Clock::get()?;
+  ctx.accounts.campaign.verify_claim_window(clock.unix_timestamp)?;
+  ctx.accounts.campaign.verify_claim_status(&ctx.accounts.claimer.key(), user_idx, &proof, nonce, clock.slot)?;
+  let amount = ctx.accounts.campaign.claimable_amount(user_idx, claim_amount)?;
+  let vested = ctx.accounts.campaign.is_vesting();
+  // Vested rewards are withdrawn by the claimer later, so they cannot be redirected
+  require!(!vested || ctx.accounts.recipient.is_none(), CommiError::InvalidRecipient);
+  if vested {
+    ctx.accounts.vest_tokens(amount, ctx.bumps.vesting.unwrap_or_default())?;
+  } else {
+    ctx.accounts.claim_tokens(amount, ctx.bumps.campaign)?;
+  }
+  let remaining = ctx.accounts.update_status(user_idx, amount)?;
+  let recipient = ctx.accounts.recipient.as_ref().map_or(ctx.accounts.claimer.key(), |recipient| recipient.key());
   emit!(ClaimEvent {
     claimer: ctx.accounts.claimer.key(),
     campaign: ctx.accounts.campaign.key(),
-    amount
+    recipient,
+    amount,
+    remaining,
+    vested,
   });
   Ok(())
 }
-
@@ -0,0 +1,121 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::{create_idempotent, get_associated_token_address_with_program_id, AssociatedToken, Create};
+use anchor_spl::token_interface::{TokenInterface, Mint};
+use crate::state::{CampaignState, Config, PAUSE_CLAIM};
+use crate::errors::CommiError;
+use crate::events::ClaimEvent;
+
+// Remaining accounts per claim: campaign, mint, vault, claimer_ata
+pub const CLAIM_MANY_GROUP_LEN: usize = 4;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ClaimArgs {
+  pub user_idx: u64,
+  pub proof: Vec<[u8; 32]>,
+  pub nonce: u64,
+  pub claim_amount: u64,
+}
+
+#[derive(Accounts)]
+pub struct ClaimMany<'info> {
+  #[account(mut)]
+  pub claimer: Signer<'info>,
+
+  #[account(
+    seeds = [b"config"],
+    bump = config.bump,
+  )]
+  pub config: Account<'info, Config>,
+
+  pub associated_token_program: Program<'info, AssociatedToken>,
+  pub token_program: Interface<'info, TokenInterface>,
+  pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimMany<'info> {
+
+  fn create_claimer_ata(&self, mint: &AccountInfo<'info>, claimer_ata: &AccountInfo<'info>) -> Result<()> {
+    create_idempotent(CpiContext::new(
+      self.associated_token_program.to_account_info(),
+      Create {
+        payer: self.claimer.to_account_info(),
+        associated_token: claimer_ata.clone(),
+        authority: self.claimer.to_account_info(),
+        mint: mint.clone(),
+        system_program: self.system_program.to_account_info(),
+        token_program: self.token_program.to_account_info(),
+      },
+    ))
+  }
+
+  fn claim_one(&self, group: &'info [AccountInfo<'info>], args: &ClaimArgs, clock: &Clock) -> Result<()> {
+    let [campaign_info, mint_info, vault_info, claimer_ata_info] = group else {
+      return err!(CommiError::InvalidClaimAccounts);
+    };
+    require!(campaign_info.is_writable, CommiError::InvalidClaimAccounts);
+    let mut campaign: Account<'info, CampaignState> = Account::try_from(campaign_info)?;
+    require_keys_eq!(campaign.mint, mint_info.key(), CommiError::InvalidMint);
+    let mint: InterfaceAccount<'info, Mint> = InterfaceAccount::try_from(mint_info)?;
+    require_keys_eq!(*mint_info.owner, self.token_program.key(), CommiError::InvalidMint);
+
+    let seed = campaign.seed.to_le_bytes();
+    let (campaign_key, bump) = Pubkey::find_program_address(
+      &[b"campaign", campaign.launcher.as_ref(), campaign.mint.as_ref(), seed.as_ref()],
+      &crate::ID,
+    );
+    require_keys_eq!(campaign_key, campaign_info.key(), CommiError::InvalidClaimAccounts);
+    require_keys_eq!(
+      vault_info.key(),
+      get_associated_token_address_with_program_id(&campaign_key, &mint.key(), &self.token_program.key()),
+      CommiError::InvalidClaimAccounts
+    );
+    require_keys_eq!(
+      claimer_ata_info.key(),
+      get_associated_token_address_with_program_id(&self.claimer.key(), &mint.key(), &self.token_program.key()),
+      CommiError::InvalidClaimAccounts
+    );
+
+    require_eq!(campaign.closed, 0, CommiError::CampaignClosed);
+    require_eq!(campaign.locked, 0, CommiError::CampaignLocked);
+    // Vesting campaigns need a vesting account per claimer, so they go through `claim`
+    require!(!campaign.is_vesting(), CommiError::VestingAccountRequired);
+    campaign.verify_claim_window(clock.unix_timestamp)?;
+    campaign.verify_claim_status(&self.claimer.key(), args.user_idx, &args.proof, args.nonce, clock.slot)?;
+
+    self.create_claimer_ata(mint_info, claimer_ata_info)?;
+    let amount = campaign.claimable_amount(args.user_idx, args.claim_amount)?;
+    let remaining = campaign.take_reward(args.user_idx, amount);
+    CampaignState::transfer_from_vault(
+      &campaign,
+      bump,
+      vault_info.clone(),
+      &mint,
+      claimer_ata_info.clone(),
+      self.token_program.to_account_info(),
+      amount,
+    )?;
+    // Persist now so a campaign repeated later in the batch sees the reduced reward
+    campaign.exit(&crate::ID)?;
+
+    emit!(ClaimEvent {
+      claimer: self.claimer.key(),
+      campaign: campaign_key,
+      recipient: self.claimer.key(),
+      amount,
+      remaining,
+      vested: false,
+    });
+    Ok(())
+  }
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, ClaimMany<'info>>, claims: Vec<ClaimArgs>) -> Result<()> {
+  require!(!ctx.accounts.config.is_paused(PAUSE_CLAIM), CommiError::ProgramPaused);
+  require!(!claims.is_empty(), CommiError::InvalidClaimAccounts);
+  require_eq!(ctx.remaining_accounts.len(), claims.len() * CLAIM_MANY_GROUP_LEN, CommiError::InvalidClaimAccounts);
+  let clock = Clock::get()?;
+  for (group, args) in ctx.remaining_accounts.chunks(CLAIM_MANY_GROUP_LEN).zip(claims.iter()) {
+    ctx.accounts.claim_one(group, args, &clock)?;
+  }
+  Ok(())
+}
@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{TokenInterface, TokenAccount, Mint};
+use crate::state::{CampaignState, Config, PAUSE_WITHDRAW};
+use crate::errors::CommiError;
+use crate::events::ClawbackEvent;
+
+#[derive(Accounts)]
+pub struct Clawback<'info> {
+  #[account(mut)]
+  pub launcher: Signer<'info>,
+
+  #[account(
+    seeds = [b"config"],
+    bump = config.bump,
+  )]
+  pub config: Account<'info, Config>,
+
+  #[account(
+    mut,
+    seeds = [b"campaign", launcher.key().as_ref(), mint.key().as_ref(), campaign.seed.to_le_bytes().as_ref()],
+    bump,
+    has_one = mint @ CommiError::InvalidMint,
+    has_one = launcher @ CommiError::InvalidLauncher
+  )]
+  pub campaign: Account<'info, CampaignState>,
+  pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+  #[account(
+    mut,
+    associated_token::mint = mint,
+    associated_token::authority = campaign,
+    associated_token::token_program = token_program
+  )]
+  pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+  #[account(
+    mut,
+    associated_token::mint = mint,
+    associated_token::authority = launcher,
+    associated_token::token_program = token_program
+  )]
+  pub launcher_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+  pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> Clawback<'info> {
+
+  fn withdraw_tokens(&self, amount: u64, bump: u8) -> Result<()> {
+    CampaignState::transfer_from_vault(
+      &self.campaign,
+      bump,
+      self.vault.to_account_info(),
+      &self.mint,
+      self.launcher_ata.to_account_info(),
+      self.token_program.to_account_info(),
+      amount,
+    )
+  }
+
+  fn close_campaign(&mut self) -> Result<()> {
+    self.campaign.rewards.iter_mut().for_each(|reward| *reward = 0);
+    self.campaign.claimed.iter_mut().for_each(|claimed| *claimed = 0);
+    self.campaign.closed = 1;
+    Ok(())
+  }
+}
+
+pub fn handler(ctx: Context<Clawback>) -> Result<()> {
+  require!(!ctx.accounts.config.is_paused(PAUSE_WITHDRAW), CommiError::ProgramPaused);
+  require_eq!(ctx.accounts.campaign.closed, 0, CommiError::CampaignClosed);
+  require_gte!(Clock::get()?.unix_timestamp, ctx.accounts.campaign.end_ts, CommiError::CampaignNotEnded);
+  // Sweep the vault (unallocated rewards[0], unclaimed allocations and any dust) but keep claimed rewards still vesting
+  let amount = ctx.accounts.vault.amount.saturating_sub(ctx.accounts.campaign.vesting_outstanding);
+  ctx.accounts.withdraw_tokens(amount, ctx.bumps.campaign)?;
+  ctx.accounts.close_campaign()?;
+  emit!(ClawbackEvent {
+    campaign: ctx.accounts.campaign.key(),
+    launcher: ctx.accounts.launcher.key(),
+    amount,
+  });
+  Ok(())
+}
@@ -0,0 +1,112 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{TokenInterface, TokenAccount, Mint};
+use crate::state::{CampaignState, Config, PAUSE_WITHDRAW};
+use crate::errors::CommiError;
+use crate::events::CloseEvent;
+
+#[derive(Accounts)]
+pub struct CloseCampaign<'info> {
+  #[account(mut)]
+  pub launcher: Signer<'info>,
+
+  // Refunded the rent paid for `extend`; this is whoever is assigned to the campaign when it closes,
+  // so `assign_distributor` hands the refund over together with the campaign
+  #[account(mut)]
+  pub distributor: SystemAccount<'info>,
+
+  #[account(
+    seeds = [b"config"],
+    bump = config.bump,
+  )]
+  pub config: Account<'info, Config>,
+
+  #[account(
+    mut,
+    close = launcher,
+    seeds = [b"campaign", launcher.key().as_ref(), mint.key().as_ref(), campaign.seed.to_le_bytes().as_ref()],
+    bump,
+    has_one = mint @ CommiError::InvalidMint,
+    has_one = launcher @ CommiError::InvalidLauncher,
+    has_one = distributor @ CommiError::InvalidDistributor
+  )]
+  pub campaign: Account<'info, CampaignState>,
+  pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+  #[account(
+    mut,
+    associated_token::mint = mint,
+    associated_token::authority = campaign,
+    associated_token::token_program = token_program
+  )]
+  pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+  #[account(
+    mut,
+    associated_token::mint = mint,
+    associated_token::authority = launcher,
+    associated_token::token_program = token_program
+  )]
+  pub launcher_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+  pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> CloseCampaign<'info> {
+
+  // Sweeps whatever is left, e.g. dust sent to the vault after clawback, so it can always be closed
+  fn sweep_vault(&self, bump: u8) -> Result<u64> {
+    let amount = self.vault.amount;
+    if amount > 0 {
+      CampaignState::transfer_from_vault(
+        &self.campaign,
+        bump,
+        self.vault.to_account_info(),
+        &self.mint,
+        self.launcher_ata.to_account_info(),
+        self.token_program.to_account_info(),
+        amount,
+      )?;
+    }
+    Ok(amount)
+  }
+
+  fn close_vault(&self, bump: u8) -> Result<()> {
+    CampaignState::close_vault(
+      &self.campaign,
+      bump,
+      self.vault.to_account_info(),
+      self.launcher.to_account_info(),
+      self.token_program.to_account_info(),
+    )
+  }
+
+  // The remaining campaign lamports go to the launcher through the `close` constraint
+  fn refund_distributor(&self) -> Result<u64> {
+    let refund = self.campaign.realloc_rent.min(self.campaign.to_account_info().lamports());
+    self.campaign.sub_lamports(refund)?;
+    self.distributor.add_lamports(refund)?;
+    Ok(refund)
+  }
+}
+
+pub fn handler(ctx: Context<CloseCampaign>) -> Result<()> {
+  require!(!ctx.accounts.config.is_paused(PAUSE_WITHDRAW), CommiError::ProgramPaused);
+  let campaign = &ctx.accounts.campaign;
+  // Nothing left in the vault is owed once the campaign is clawed back or fully claimed, unless it is still vesting
+  require!(
+    campaign.closed == 1 || (0..campaign.rewards.len()).all(|idx| campaign.outstanding(idx) == 0),
+    CommiError::CampaignNotEmpty
+  );
+  require_eq!(campaign.vesting_outstanding, 0, CommiError::CampaignNotEmpty);
+  let swept = ctx.accounts.sweep_vault(ctx.bumps.campaign)?;
+  ctx.accounts.close_vault(ctx.bumps.campaign)?;
+  let distributor_refund = ctx.accounts.refund_distributor()?;
+  emit!(CloseEvent {
+    campaign: ctx.accounts.campaign.key(),
+    launcher: ctx.accounts.launcher.key(),
+    distributor: ctx.accounts.distributor.key(),
+    distributor_refund,
+    swept,
+  });
+  Ok(())
+}
@@ -0,0 +1,268 @@
+use anchor_lang::prelude::*;
+use crate::program::CommiMerkle;
+use crate::state::{
+  CampaignState, Config, DEFAULT_MAX_CONF_BPS, FALLBACK_PULL, FALLBACK_PUSH, MAX_CONF_BPS, MAX_DISTRIBUTORS, MIN_VERIFICATION_FULL,
+  PAUSE_ALL
+};
+use crate::errors::CommiError;
+use crate::events::{
+  AcceptAdminEvent, AcceptDistributorEvent, AssignDistributorEvent, FallbackFeedEvent, OraclePolicyEvent, PauseEvent,
+  PriceFeedEvent, ProposeAdminEvent, ProposeDistributorEvent, RemoveDistributorEvent, UnpauseEvent
+};
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+  #[account(mut)]
+  pub admin: Signer<'info>,
+
+  #[account(
+    init,
+    payer = admin,
+    space = 32 + 32 + 32 + 32 + 32 + 1 + 1 + 2 + 1 + 1 + 4 + 32 * MAX_DISTRIBUTORS + Config::DISCRIMINATOR.len(),
+    seeds = [b"config"],
+    bump,
+  )]
+  pub config: Account<'info, Config>,
+
+  // Only the upgrade authority may create the config, so it cannot be front-run after deployment
+  #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+  pub program: Program<'info, CommiMerkle>,
+
+  #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ CommiError::InvalidAdmin)]
+  pub program_data: Account<'info, ProgramData>,
+
+  pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+  pub admin: Signer<'info>,
+
+  #[account(
+    mut,
+    seeds = [b"config"],
+    bump = config.bump,
+    has_one = admin @ CommiError::InvalidAdmin,
+  )]
+  pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct AssignDistributor<'info> {
+  pub admin: Signer<'info>,
+
+  pub distributor: SystemAccount<'info>,
+
+  #[account(
+    seeds = [b"config"],
+    bump = config.bump,
+    has_one = admin @ CommiError::InvalidAdmin,
+    constraint = config.distributors.contains(&distributor.key()) @ CommiError::InvalidDistributor,
+  )]
+  pub config: Account<'info, Config>,
+
+  #[account(mut)]
+  pub campaign: Account<'info, CampaignState>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+  pub pending_admin: Signer<'info>,
+
+  #[account(
+    mut,
+    seeds = [b"config"],
+    bump = config.bump,
+    constraint = config.pending_admin == pending_admin.key() @ CommiError::InvalidPendingAdmin,
+  )]
+  pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptDistributor<'info> {
+  pub pending_distributor: Signer<'info>,
+
+  #[account(
+    mut,
+    seeds = [b"config"],
+    bump = config.bump,
+    constraint = config.pending_distributor == pending_distributor.key() @ CommiError::InvalidPendingDistributor,
+  )]
+  pub config: Account<'info, Config>,
+}
+
+impl<'info> InitializeConfig<'info> {
+  fn populate_config(&mut self, distributor: Pubkey, price_feed: Pubkey, bump: u8) -> Result<()> {
+    self.config.set_inner(Config {
+      admin: self.admin.key(),
+      pending_admin: Pubkey::default(),
+      pending_distributor: Pubkey::default(),
+      price_feed,
+      fallback_feed: Pubkey::default(),
+      bump,
+      paused: 0,
+      max_conf_bps: DEFAULT_MAX_CONF_BPS,
+      min_verification: MIN_VERIFICATION_FULL,
+      fallback_kind: FALLBACK_PULL,
+      distributors: vec![distributor],
+    });
+    Ok(())
+  }
+}
+
+impl<'info> AcceptAdmin<'info> {
+  fn accept(&mut self) -> Result<Pubkey> {
+    let previous_admin = self.config.admin;
+    self.config.admin = self.pending_admin.key();
+    self.config.pending_admin = Pubkey::default();
+    Ok(previous_admin)
+  }
+}
+
+impl<'info> UpdateConfig<'info> {
+  fn remove_distributor(&mut self, distributor: Pubkey) -> Result<()> {
+    let position = self.config.distributors
+      .iter()
+      .position(|key| *key == distributor)
+      .ok_or(CommiError::InvalidDistributor)?;
+    self.config.distributors.swap_remove(position);
+    Ok(())
+  }
+}
+
+impl<'info> AcceptDistributor<'info> {
+  fn accept(&mut self) -> Result<()> {
+    let distributor = self.pending_distributor.key();
+    require!(!self.config.distributors.contains(&distributor), CommiError::DuplicateDistributor);
+    require_gt!(MAX_DISTRIBUTORS, self.config.distributors.len(), CommiError::TooManyDistributors);
+    self.config.distributors.push(distributor);
+    self.config.pending_distributor = Pubkey::default();
+    Ok(())
+  }
+}
+
+impl<'info> AssignDistributor<'info> {
+  fn assign(&mut self) -> Result<Pubkey> {
+    let previous_distributor = self.campaign.distributor;
+    self.campaign.distributor = self.distributor.key();
+    Ok(previous_distributor)
+  }
+}
+
+pub fn initialize(ctx: Context<InitializeConfig>, distributor: Pubkey, price_feed: Pubkey) -> Result<()> {
+  ctx.accounts.populate_config(distributor, price_feed, ctx.bumps.config)?;
+  Ok(())
+}
+
+// Proposing the default pubkey cancels a pending rotation
+pub fn propose_admin(ctx: Context<UpdateConfig>, pending_admin: Pubkey) -> Result<()> {
+  ctx.accounts.config.pending_admin = pending_admin;
+  emit!(ProposeAdminEvent {
+    admin: ctx.accounts.config.admin,
+    pending_admin,
+  });
+  Ok(())
+}
+
+pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+  let previous_admin = ctx.accounts.accept()?;
+  emit!(AcceptAdminEvent {
+    previous_admin,
+    admin: ctx.accounts.config.admin,
+  });
+  Ok(())
+}
+
+// Accepting adds the pending distributor to the allowlist; rotation is accept + remove
+pub fn propose_distributor(ctx: Context<UpdateConfig>, pending_distributor: Pubkey) -> Result<()> {
+  ctx.accounts.config.pending_distributor = pending_distributor;
+  emit!(ProposeDistributorEvent {
+    pending_distributor,
+  });
+  Ok(())
+}
+
+pub fn accept_distributor(ctx: Context<AcceptDistributor>) -> Result<()> {
+  ctx.accounts.accept()?;
+  emit!(AcceptDistributorEvent {
+    distributor: ctx.accounts.pending_distributor.key(),
+  });
+  Ok(())
+}
+
+pub fn remove_distributor(ctx: Context<UpdateConfig>, distributor: Pubkey) -> Result<()> {
+  ctx.accounts.remove_distributor(distributor)?;
+  emit!(RemoveDistributorEvent {
+    distributor,
+  });
+  Ok(())
+}
+
+// Moves a campaign to another allowlisted distributor, e.g. after its key was removed.
+// The `extend` rent refund paid out by `close_campaign` moves with it to the new distributor.
+pub fn assign_distributor(ctx: Context<AssignDistributor>) -> Result<()> {
+  let previous_distributor = ctx.accounts.assign()?;
+  emit!(AssignDistributorEvent {
+    campaign: ctx.accounts.campaign.key(),
+    previous_distributor,
+    distributor: ctx.accounts.distributor.key(),
+  });
+  Ok(())
+}
+
+pub fn pause(ctx: Context<UpdateConfig>, flags: u8) -> Result<()> {
+  require!(flags != 0 && flags & !PAUSE_ALL == 0, CommiError::InvalidPauseFlags);
+  ctx.accounts.config.paused |= flags;
+  emit!(PauseEvent {
+    flags,
+    paused: ctx.accounts.config.paused,
+  });
+  Ok(())
+}
+
+pub fn unpause(ctx: Context<UpdateConfig>, flags: u8) -> Result<()> {
+  require!(flags != 0 && flags & !PAUSE_ALL == 0, CommiError::InvalidPauseFlags);
+  ctx.accounts.config.paused &= !flags;
+  emit!(UnpauseEvent {
+    flags,
+    paused: ctx.accounts.config.paused,
+  });
+  Ok(())
+}
+
+pub fn set_oracle_policy(ctx: Context<UpdateConfig>, max_conf_bps: u16, min_verification: u8) -> Result<()> {
+  require!(max_conf_bps > 0 && max_conf_bps <= MAX_CONF_BPS, CommiError::InvalidOraclePolicy);
+  ctx.accounts.config.max_conf_bps = max_conf_bps;
+  ctx.accounts.config.min_verification = min_verification;
+  emit!(OraclePolicyEvent {
+    max_conf_bps,
+    min_verification,
+  });
+  Ok(())
+}
+
+// Moves pricing to another PriceUpdateV2 account, which must still publish the SOL/USD feed
+pub fn set_price_feed(ctx: Context<UpdateConfig>, price_feed: Pubkey) -> Result<()> {
+  require_keys_neq!(price_feed, Pubkey::default(), CommiError::InvalidPriceFeed);
+  require_keys_neq!(price_feed, ctx.accounts.config.fallback_feed, CommiError::InvalidPriceFeed);
+  let previous_price_feed = ctx.accounts.config.price_feed;
+  ctx.accounts.config.price_feed = price_feed;
+  emit!(PriceFeedEvent {
+    previous_price_feed,
+    price_feed,
+  });
+  Ok(())
+}
+
+// Setting the default pubkey disables the fallback
+pub fn set_fallback_feed(ctx: Context<UpdateConfig>, fallback_feed: Pubkey, fallback_kind: u8) -> Result<()> {
+  require!(fallback_kind == FALLBACK_PULL || fallback_kind == FALLBACK_PUSH, CommiError::InvalidOraclePolicy);
+  require_keys_neq!(fallback_feed, ctx.accounts.config.price_feed, CommiError::InvalidPriceFeed);
+  ctx.accounts.config.fallback_feed = fallback_feed;
+  ctx.accounts.config.fallback_kind = fallback_kind;
+  emit!(FallbackFeedEvent {
+    fallback_feed,
+    fallback_kind,
+  });
+  Ok(())
+}
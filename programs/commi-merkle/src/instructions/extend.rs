@@ -1,33 +1,61 @@
-use anchor_lang::prelude::*;
-use crate::state::CampaignState;
+use anchor_lang::{prelude::*, system_program};
+use crate::state::{CampaignState, Config, PAUSE_UPDATE};
 use crate::errors::CommiError;
 use crate::events::ExtendEvent;
 
 #[derive(Accounts)]
-#[instruction(new_participants: u64)]
 pub struct Extend<'info> {
   #[account(mut)]
   pub distributor: Signer<'info>,
+  #[account(
+    seeds = [b"config"],
+    bump = config.bump,
+    constraint = config.distributors.contains(&distributor.key()) @ CommiError::InvalidDistributor,
+  )]
+  pub config: Account<'info, Config>,
   #[account(
     mut,
-    realloc = 32 + 32 + 8 + 8 + 1 + 32 + 24 + new_participants as usize * 8 + CampaignState::DISCRIMINATOR.len(), 
-    realloc::payer = distributor,    
-    realloc::zero = false,
+    has_one = distributor @ CommiError::InvalidDistributor,
   )]
   pub campaign: Account<'info, CampaignState>,
   pub system_program: Program<'info, System>,
 }
 
 impl<'info> Extend<'info> {
-  fn extend(&mut self, new_participants: u64) -> Result<()> {
+  fn resize_campaign(&mut self, new_participants: u64) -> Result<()> {
+    let campaign_info = self.campaign.to_account_info();
+    let space = CampaignState::space(new_participants as usize);
+    let lamports_before = campaign_info.lamports();
+    let required = Rent::get()?.minimum_balance(space);
+    if required > lamports_before {
+      system_program::transfer(
+        CpiContext::new(
+          self.system_program.to_account_info(),
+          system_program::Transfer {
+            from: self.distributor.to_account_info(),
+            to: campaign_info.clone(),
+          },
+        ),
+        required - lamports_before,
+      )?;
+    }
+    campaign_info.resize(space)?;
+
+    // Track the rent actually paid by the distributor so it can be refunded when the campaign is closed
+    let paid = campaign_info.lamports() - lamports_before;
+    self.campaign.realloc_rent = self.campaign.realloc_rent.checked_add(paid).ok_or(CommiError::InvalidUpdateAmount)?;
     self.campaign.rewards.resize(new_participants as usize, 0u64);
+    self.campaign.claimed.resize(new_participants as usize, 0u64);
     Ok(())
   }
 }
 
 pub fn handler(ctx: Context<Extend>, new_participants: u64) -> Result<()> {
-  require_eq!(ctx.accounts.distributor.key(), Pubkey::from_str_const("5PpeUwd8XqJ4y75gEM3ATrmaV4piR9GdZhpuFhH76UGw"), CommiError::InvalidDistributor);
-  ctx.accounts.extend(new_participants)?;
+  require!(!ctx.accounts.config.is_paused(PAUSE_UPDATE), CommiError::ProgramPaused);
+  require_eq!(ctx.accounts.campaign.closed, 0, CommiError::CampaignClosed);
+  // Shrinking would drop allocations that are still claimable
+  require_gte!(new_participants as usize, ctx.accounts.campaign.rewards.len(), CommiError::CannotShrinkCampaign);
+  ctx.accounts.resize_campaign(new_participants)?;
   emit!(ExtendEvent {
     size: new_participants,
     campaign: ctx.accounts.campaign.key(),
@@ -0,0 +1,259 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use pyth_solana_receiver_sdk::price_update::{get_feed_id_from_hex, Price, PriceUpdateV2};
+use crate::state::{Config, FeeConfig, FALLBACK_PUSH, MAX_FEE_MINTS, MAX_FEE_WAIVERS};
+use crate::errors::CommiError;
+use crate::events::FeeConfigUpdated;
+
+pub const MAXIMUM_AGE: u64 = 60;
+// The feed id stays fixed because `fee_lamports` converts USD to lamports and only makes sense for SOL/USD;
+// `set_price_feed` changes which account publishes it.
+pub const FEED_ID: &str = "0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d"; // SOL/USD price feed id from https://pyth.network/developers/price-feed-ids
+
+// Legacy Pyth push (`pc::Price`) account layout, as in mock-pyth-push
+const PUSH_MAGIC: u32 = 0xa1b2_c3d4;
+const PUSH_ACCOUNT_TYPE_PRICE: u32 = 3;
+const PUSH_STATUS_TRADING: u32 = 1;
+const PUSH_EXPO_OFFSET: usize = 20;
+const PUSH_TIMESTAMP_OFFSET: usize = 96;
+const PUSH_AGG_OFFSET: usize = 208;
+const PUSH_AGG_LEN: usize = 32;
+
+fn pull_price(config: &Config, price_update: &PriceUpdateV2) -> Result<Price> {
+  require!(
+    price_update.verification_level.gte(config.min_verification_level()),
+    CommiError::InsufficientPriceVerification
+  );
+  let price = price_update
+    .get_price_unchecked(&get_feed_id_from_hex(FEED_ID)?)
+    .map_err(|_| CommiError::InvalidPriceFeed)?;
+  Ok(price)
+}
+
+fn push_price(account: &AccountInfo) -> Result<Price> {
+  let data = account.try_borrow_data()?;
+  require_gte!(data.len(), PUSH_AGG_OFFSET + PUSH_AGG_LEN, CommiError::InvalidPriceFeed);
+  let u32_at = |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+  let u64_at = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+  require!(u32_at(0) == PUSH_MAGIC && u32_at(8) == PUSH_ACCOUNT_TYPE_PRICE, CommiError::InvalidPriceFeed);
+  require_eq!(u32_at(PUSH_AGG_OFFSET + 16), PUSH_STATUS_TRADING, CommiError::PriceNotTrading);
+  Ok(Price {
+    price: u64_at(PUSH_AGG_OFFSET) as i64,
+    conf: u64_at(PUSH_AGG_OFFSET + 8),
+    exponent: u32_at(PUSH_EXPO_OFFSET) as i32,
+    publish_time: u64_at(PUSH_TIMESTAMP_OFFSET) as i64,
+  })
+}
+
+fn is_fresh(price: &Price) -> Result<bool> {
+  Ok(price.publish_time.saturating_add(MAXIMUM_AGE as i64) >= Clock::get()?.unix_timestamp)
+}
+
+// SOL/USD price checked against the config's oracle policy, read from the primary feed
+// or, when that is stale, from the configured fallback. Returns the account priced from.
+pub fn sol_usd_price(config: &Config, price_update: &Account<PriceUpdateV2>, fallback: Option<&UncheckedAccount>) -> Result<(Price, Pubkey)> {
+  let primary = pull_price(config, price_update)?;
+  let (price, source) = match fallback {
+    Some(fallback) if !is_fresh(&primary)? => {
+      let price = if config.fallback_kind == FALLBACK_PUSH {
+        push_price(fallback)?
+      } else {
+        require_keys_eq!(*fallback.owner, pyth_solana_receiver_sdk::ID, CommiError::InvalidPriceFeed);
+        let fallback_update = PriceUpdateV2::try_deserialize(&mut &fallback.try_borrow_data()?[..])?;
+        pull_price(config, &fallback_update)?
+      };
+      (price, fallback.key())
+    }
+    _ => (primary, price_update.key()),
+  };
+  require!(is_fresh(&price)?, CommiError::StalePrice);
+  require_gt!(price.price, 0, CommiError::NonPositivePrice);
+  require!(config.is_confident(price.price, price.conf), CommiError::PriceConfidenceTooWide);
+  Ok((price, source))
+}
+
+// Lamports `launcher` pays to launch at the current SOL/USD price, with the price account used
+pub fn service_fee(
+  config: &Config,
+  fee_config: &FeeConfig,
+  price_update: &Account<PriceUpdateV2>,
+  fallback: Option<&UncheckedAccount>,
+  launcher: &Pubkey,
+) -> Result<(u64, Pubkey)> {
+  if fee_config.is_waived(launcher) {
+    return Ok((0, Pubkey::default()));
+  }
+  let (price, source) = sol_usd_price(config, price_update, fallback)?;
+  let fee = fee_config.fee_lamports(price.price, price.exponent).ok_or(CommiError::InvalidServiceFee)?;
+  Ok((fee, source))
+}
+
+// Base units of the whitelisted `fee_mint` that `launcher` pays instead of SOL
+pub fn token_service_fee(fee_config: &FeeConfig, fee_mint: &InterfaceAccount<Mint>, launcher: &Pubkey) -> Result<u64> {
+  require!(fee_config.accepts_mint(&fee_mint.key()), CommiError::InvalidFeeMint);
+  if fee_config.is_waived(launcher) {
+    return Ok(0);
+  }
+  let fee = fee_config.fee_tokens(fee_mint.decimals).ok_or(CommiError::InvalidServiceFee)?;
+  Ok(fee)
+}
+
+#[derive(Accounts)]
+pub struct UpdateFeeConfig<'info> {
+  #[account(mut)]
+  pub admin: Signer<'info>,
+
+  #[account(
+    seeds = [b"config"],
+    bump = config.bump,
+    has_one = admin @ CommiError::InvalidAdmin,
+  )]
+  pub config: Account<'info, Config>,
+
+  #[account(
+    init_if_needed,
+    payer = admin,
+    space = 32 + 8 + 1 + 4 + 32 * MAX_FEE_WAIVERS + 4 + 32 * MAX_FEE_MINTS + FeeConfig::DISCRIMINATOR.len(),
+    seeds = [b"fee_config"],
+    bump,
+  )]
+  pub fee_config: Account<'info, FeeConfig>,
+
+  pub system_program: Program<'info, System>,
+}
+
+impl<'info> UpdateFeeConfig<'info> {
+  fn update(&mut self, fee_receiver: Pubkey, fee_usd_cents: u64, waivers: Vec<Pubkey>, fee_mints: Vec<Pubkey>, bump: u8) -> Result<()> {
+    self.fee_config.set_inner(FeeConfig {
+      fee_receiver,
+      fee_usd_cents,
+      bump,
+      waivers,
+      fee_mints,
+    });
+    Ok(())
+  }
+}
+
+// Replaces the whole fee schedule; launchers in `waivers` launch without paying the fee,
+// and `fee_mints` are USD stablecoins the fee may be paid in instead of SOL
+pub fn update_fee_config(
+  ctx: Context<UpdateFeeConfig>,
+  fee_receiver: Pubkey,
+  fee_usd_cents: u64,
+  waivers: Vec<Pubkey>,
+  fee_mints: Vec<Pubkey>,
+) -> Result<()> {
+  require_keys_neq!(fee_receiver, Pubkey::default(), CommiError::InvalidFeeReceiver);
+  require_gte!(MAX_FEE_WAIVERS, waivers.len(), CommiError::TooManyFeeWaivers);
+  require_gte!(MAX_FEE_MINTS, fee_mints.len(), CommiError::TooManyFeeMints);
+  ctx.accounts.update(fee_receiver, fee_usd_cents, waivers.clone(), fee_mints.clone(), ctx.bumps.fee_config)?;
+  emit!(FeeConfigUpdated {
+    fee_receiver,
+    fee_usd_cents,
+    waivers,
+    fee_mints,
+  });
+  Ok(())
+}
+
+#[derive(Accounts)]
+pub struct QuoteFee<'info> {
+  #[account(
+    seeds = [b"config"],
+    bump = config.bump,
+  )]
+  pub config: Account<'info, Config>,
+
+  #[account(
+    seeds = [b"fee_config"],
+    bump = fee_config.bump,
+  )]
+  pub fee_config: Account<'info, FeeConfig>,
+
+  #[account(
+    address = config.price_feed @ CommiError::InvalidPriceFeed
+  )]
+  pub price_update: Account<'info, PriceUpdateV2>,
+
+  /// CHECK: must be the configured fallback feed, parsed according to `config.fallback_kind`
+  #[account(
+    address = config.fallback_feed @ CommiError::InvalidPriceFeed
+  )]
+  pub fallback_price_update: Option<UncheckedAccount<'info>>,
+
+  // Quotes in this stablecoin's base units instead of lamports
+  pub fee_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+}
+
+// Read-only: simulate it and read the fee `launch` would charge from the return data
+pub fn quote_fee(ctx: Context<QuoteFee>, launcher: Pubkey) -> Result<u64> {
+  let accounts = &ctx.accounts;
+  if let Some(fee_mint) = &accounts.fee_mint {
+    return token_service_fee(&accounts.fee_config, fee_mint, &launcher);
+  }
+  let (fee, _) = service_fee(
+    &accounts.config,
+    &accounts.fee_config,
+    &accounts.price_update,
+    accounts.fallback_price_update.as_ref(),
+    &launcher,
+  )?;
+  Ok(fee)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use mock_pyth_push::pc;
+
+  fn push_account() -> pc::Price {
+    let mut price = pc::Price {
+      magic: PUSH_MAGIC,
+      atype: PUSH_ACCOUNT_TYPE_PRICE,
+      expo: -8,
+      timestamp: 1_700_000_000,
+      ..Default::default()
+    };
+    price.agg.price = 20_000_000_000;
+    price.agg.conf = 10_000_000;
+    price.agg.status = pc::PriceStatus::Trading;
+    price
+  }
+
+  fn read(price: &pc::Price) -> Result<Price> {
+    let key = Pubkey::new_unique();
+    let owner = mock_pyth_push::ID;
+    let mut lamports = 0;
+    let mut data = bytemuck::bytes_of(price).to_vec();
+    let account = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+    push_price(&account)
+  }
+
+  #[test]
+  fn push_price_reads_the_pc_price_layout() {
+    let price = read(&push_account()).unwrap();
+    assert_eq!(price.price, 20_000_000_000);
+    assert_eq!(price.conf, 10_000_000);
+    assert_eq!(price.exponent, -8);
+    assert_eq!(price.publish_time, 1_700_000_000);
+  }
+
+  #[test]
+  fn push_price_rejects_a_halted_aggregate() {
+    let mut price = push_account();
+    price.agg.status = pc::PriceStatus::Halted;
+    assert_eq!(read(&price).unwrap_err(), CommiError::PriceNotTrading.into());
+  }
+
+  #[test]
+  fn push_price_rejects_other_accounts() {
+    let mut price = push_account();
+    price.magic = 0;
+    assert_eq!(read(&price).unwrap_err(), CommiError::InvalidPriceFeed.into());
+
+    let mut price = push_account();
+    price.atype = 2;
+    assert_eq!(read(&price).unwrap_err(), CommiError::InvalidPriceFeed.into());
+  }
+}
@@ -5,10 +5,11 @@ use anchor_spl::{
   token::{transfer_checked, TransferChecked}, 
   token_interface::{Mint, TokenAccount, TokenInterface}
 };
-use pyth_solana_receiver_sdk::price_update::{get_feed_id_from_hex, PriceUpdateV2};
-use crate::state::CampaignState;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+use crate::state::{CampaignSchedule, CampaignState, Config, FeeConfig, HASH_ALGO_KECCAK256, HASH_ALGO_SHA256, HASH_VERSION_PREFIXED, PAUSE_LAUNCH};
 use crate::errors::CommiError;
 use crate::events::LaunchEvent;
+use crate::instructions::fee::{service_fee, token_service_fee};
 
 #[derive(Accounts)]
 #[instruction(seed: u64)]
@@ -16,14 +17,30 @@ pub struct Launch<'info> {
   #[account(mut)]
   pub launcher: Signer<'info>,
 
-  #[account(mut)]
   pub distributor: SystemAccount<'info>,
+
+  #[account(mut)]
+  pub fee_receiver: SystemAccount<'info>,
+
+  #[account(
+    seeds = [b"config"],
+    bump = config.bump,
+    constraint = config.distributors.contains(&distributor.key()) @ CommiError::InvalidDistributor,
+  )]
+  pub config: Account<'info, Config>,
+
+  #[account(
+    seeds = [b"fee_config"],
+    bump = fee_config.bump,
+    has_one = fee_receiver @ CommiError::InvalidFeeReceiver,
+  )]
+  pub fee_config: Account<'info, FeeConfig>,
   
   #[account(
     init,
     payer = launcher,
     // Allocate 31 participants + 1 funder at the beginning
-    space = 32 + 32 + 8 + 8 + 1 + 32 + 24 + 32 * 8 + CampaignState::DISCRIMINATOR.len(), 
+    space = CampaignState::space(32),
     seeds = [b"campaign", launcher.key().as_ref(), mint.key().as_ref(), seed.to_le_bytes().as_ref()],
     bump,
   )]
@@ -51,33 +68,68 @@ pub struct Launch<'info> {
   )]
   pub vault: InterfaceAccount<'info, TokenAccount>,
   
-  #[account(mut)]
+  #[account(
+    mut,
+    address = config.price_feed @ CommiError::InvalidPriceFeed
+  )]
   pub price_update: Account<'info, PriceUpdateV2>,
 
+  /// CHECK: must be the configured fallback feed, only read when `price_update` is stale
+  #[account(
+    address = config.fallback_feed @ CommiError::InvalidPriceFeed
+  )]
+  pub fallback_price_update: Option<UncheckedAccount<'info>>,
+
+  // Whitelisted stablecoin paying the service fee instead of SOL
+  #[account(
+    mint::token_program = token_program
+  )]
+  pub fee_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+  #[account(
+    mut,
+    associated_token::mint = fee_mint,
+    associated_token::authority = launcher,
+    associated_token::token_program = token_program
+  )]
+  pub launcher_fee_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+  #[account(
+    init_if_needed,
+    payer = launcher,
+    associated_token::mint = fee_mint,
+    associated_token::authority = fee_receiver,
+    associated_token::token_program = token_program
+  )]
+  pub fee_receiver_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
   pub associated_token_program: Program<'info, AssociatedToken>,
   pub token_program: Interface<'info, TokenInterface>,
   pub system_program: Program<'info, System>,
 }
 
-pub const MAXIMUM_AGE: u64 = 60;
-pub const FEED_ID: &str = "0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d"; // SOL/USD price feed id from https://pyth.network/developers/price-feed-ids
-
 impl<'info> Launch<'info> {
 
-  fn service_fee_calculation(&mut self) -> Result<u64> {
-    let price_update = &self.price_update;
-    let price = price_update.get_price_no_older_than(
-      &Clock::get()?,
-      MAXIMUM_AGE,
-      &get_feed_id_from_hex(FEED_ID)?,
+  // Returns the fee, the price account it was priced from and the mint it is paid in
+  fn service_fee_calculation(&self) -> Result<(u64, Pubkey, Pubkey)> {
+    if let Some(fee_mint) = &self.fee_mint {
+      let fee = token_service_fee(&self.fee_config, fee_mint, &self.launcher.key())?;
+      return Ok((fee, Pubkey::default(), fee_mint.key()));
+    }
+    let (fee, price_feed) = service_fee(
+      &self.config,
+      &self.fee_config,
+      &self.price_update,
+      self.fallback_price_update.as_ref(),
+      &self.launcher.key(),
     )?;
-    let service_fee_in_usd = 5_f64;
-    let service_fee_in_lamports = 
-      (service_fee_in_usd / ((price.price.abs() as f64) * 10f64.powi(price.exponent)) * 1_000_000_000_f64).round() as u64; 
-    Ok(service_fee_in_lamports)
+    Ok((fee, price_feed, Pubkey::default()))
   }
 
   fn transfer_service_fee(&self, service_fee: u64) -> Result<()> {
+    if let Some(fee_mint) = &self.fee_mint {
+      return self.transfer_token_service_fee(fee_mint, service_fee);
+    }
     let launcher_lamports = self.launcher.lamports();
     if launcher_lamports < service_fee {
       return err!(CommiError::InsufficientBalance);
@@ -88,7 +140,7 @@ impl<'info> Launch<'info> {
         self.system_program.to_account_info(),
         system_program::Transfer {
           from: self.launcher.to_account_info(),
-          to: self.distributor.to_account_info(),
+          to: self.fee_receiver.to_account_info(),
         },
       ),
       service_fee
@@ -96,17 +148,54 @@ impl<'info> Launch<'info> {
     Ok(())
   }
 
-  fn populate_campaign(&mut self, seed: u64, fund: u64) -> Result<()> {
+  fn transfer_token_service_fee(&self, fee_mint: &InterfaceAccount<'info, Mint>, service_fee: u64) -> Result<()> {
+    let (Some(launcher_fee_ata), Some(fee_receiver_ata)) = (&self.launcher_fee_ata, &self.fee_receiver_ata) else {
+      return err!(CommiError::FeeTokenAccountsRequired);
+    };
+    require_gte!(launcher_fee_ata.amount, service_fee, CommiError::InsufficientBalance);
+    transfer_checked(
+      CpiContext::new(
+        self.token_program.to_account_info(),
+        TransferChecked {
+          from: launcher_fee_ata.to_account_info(),
+          mint: fee_mint.to_account_info(),
+          to: fee_receiver_ata.to_account_info(),
+          authority: self.launcher.to_account_info(),
+        },
+      ),
+      service_fee,
+      fee_mint.decimals
+    )?;
+    Ok(())
+  }
+
+  fn populate_campaign(&mut self, seed: u64, fund: u64, schedule: &CampaignSchedule, hash_algo: u8) -> Result<()> {
     let mut rewards = vec![0u64; 32];
     rewards[0] = fund;
     self.campaign.set_inner(CampaignState {
       merkle_root: [0u8; 32],
+      root_grace_slots: schedule.root_grace_slots,
+      root_history_idx: 0,
+      root_history: Default::default(),
       launcher: self.launcher.key(),
       mint: self.mint.key(),
+      distributor: self.distributor.key(),
       seed,
+      start_ts: schedule.start_ts,
+      end_ts: schedule.end_ts,
+      claim_open_ts: schedule.claim_open_ts,
+      claim_close_ts: schedule.claim_close_ts,
+      vesting_cliff: schedule.vesting_cliff,
+      vesting_duration: schedule.vesting_duration,
+      vesting_outstanding: 0,
+      realloc_rent: 0,
       locked: 0,
+      closed: 0,
+      hash_version: HASH_VERSION_PREFIXED,
+      hash_algo,
       fund,
       rewards,
+      claimed: vec![0u64; 32],
     });
     Ok(())
   }
@@ -130,23 +219,39 @@ impl<'info> Launch<'info> {
 
 }
 
-pub fn handler(ctx: Context<Launch>, seed: u64, fund: u64) -> Result<()> {
+pub fn handler(ctx: Context<Launch>, seed: u64, fund: u64, schedule: CampaignSchedule, hash_algo: u8) -> Result<()> {
+  require!(!ctx.accounts.config.is_paused(PAUSE_LAUNCH), CommiError::ProgramPaused);
   let minimum = 10u64.checked_pow(ctx.accounts.mint.decimals as u32)
     .ok_or(CommiError::InvalidFund)?
     .checked_mul(10000)
     .ok_or(CommiError::InvalidFund)?;
   require_gte!(fund, minimum, CommiError::InvalidFund);
-  require_eq!(ctx.accounts.distributor.key(), Pubkey::from_str_const("5PpeUwd8XqJ4y75gEM3ATrmaV4piR9GdZhpuFhH76UGw"), CommiError::InvalidDistributor);
-  require_eq!(ctx.accounts.price_update.key(), Pubkey::from_str_const("7UVimffxr9ow1uXYxsr4LHAcV58mLzhmwaeKvJ1pjLiE"), CommiError::InvalidPriceFeed);
-  let service_fee = ctx.accounts.service_fee_calculation()?;
-  ctx.accounts.transfer_service_fee(service_fee)?;
-  ctx.accounts.populate_campaign(seed, fund)?;
+  require!(schedule.is_valid(Clock::get()?.unix_timestamp), CommiError::InvalidSchedule);
+  require!(hash_algo == HASH_ALGO_SHA256 || hash_algo == HASH_ALGO_KECCAK256, CommiError::InvalidHashAlgo);
+  let (service_fee, price_feed, fee_mint) = ctx.accounts.service_fee_calculation()?;
+  if service_fee > 0 {
+    ctx.accounts.transfer_service_fee(service_fee)?;
+  }
+  ctx.accounts.populate_campaign(seed, fund, &schedule, hash_algo)?;
   ctx.accounts.deposit_tokens(fund)?;
   emit!(LaunchEvent { 
     launcher: ctx.accounts.launcher.key(), 
     fund, 
     seed,
+    service_fee,
+    price_feed,
+    fee_mint,
     mint:  ctx.accounts.mint.key(),
+    distributor: ctx.accounts.distributor.key(),
+    start_ts: schedule.start_ts,
+    end_ts: schedule.end_ts,
+    claim_open_ts: schedule.claim_open_ts,
+    claim_close_ts: schedule.claim_close_ts,
+    vesting_cliff: schedule.vesting_cliff,
+    vesting_duration: schedule.vesting_duration,
+    root_grace_slots: schedule.root_grace_slots,
+    hash_version: HASH_VERSION_PREFIXED,
+    hash_algo,
   });
   Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,128 @@
+use anchor_lang::{prelude::*, system_program};
+use crate::state::{CampaignState, Config, HASH_ALGO_SHA256, HASH_VERSION_LEGACY};
+use crate::errors::CommiError;
+use crate::events::MigrateEvent;
+
+// Campaign layout written before any field was added to CampaignState
+#[derive(AnchorDeserialize)]
+struct LegacyCampaignState {
+  launcher: Pubkey,
+  mint: Pubkey,
+  fund: u64,
+  seed: u64,
+  locked: u8,
+  merkle_root: [u8; 32],
+  rewards: Vec<u64>,
+}
+
+impl LegacyCampaignState {
+  fn space(participants: usize) -> usize {
+    32 + 32 + 8 + 8 + 1 + 32 + 24 + participants * 8 + CampaignState::DISCRIMINATOR.len()
+  }
+}
+
+#[derive(Accounts)]
+pub struct MigrateCampaign<'info> {
+  pub admin: Signer<'info>,
+
+  // Pays for the larger account, and is refunded like for `extend` when the campaign is closed
+  #[account(mut)]
+  pub distributor: Signer<'info>,
+
+  #[account(
+    seeds = [b"config"],
+    bump = config.bump,
+    has_one = admin @ CommiError::InvalidAdmin,
+    constraint = config.distributors.contains(&distributor.key()) @ CommiError::InvalidDistributor,
+  )]
+  pub config: Account<'info, Config>,
+
+  /// CHECK: a legacy campaign does not deserialize as CampaignState, so its layout and address are checked in `read_legacy`
+  #[account(mut, owner = crate::ID)]
+  pub campaign: UncheckedAccount<'info>,
+
+  pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateCampaign<'info> {
+
+  fn read_legacy(&self) -> Result<LegacyCampaignState> {
+    let data = self.campaign.try_borrow_data()?;
+    require!(data.starts_with(CampaignState::DISCRIMINATOR), CommiError::NotLegacyCampaign);
+    let legacy = LegacyCampaignState::deserialize(&mut &data[CampaignState::DISCRIMINATOR.len()..])
+      .map_err(|_| error!(CommiError::NotLegacyCampaign))?;
+    require_eq!(data.len(), LegacyCampaignState::space(legacy.rewards.len()), CommiError::NotLegacyCampaign);
+    let (campaign_key, _) = Pubkey::find_program_address(
+      &[b"campaign", legacy.launcher.as_ref(), legacy.mint.as_ref(), legacy.seed.to_le_bytes().as_ref()],
+      &crate::ID,
+    );
+    require_keys_eq!(campaign_key, self.campaign.key(), CommiError::NotLegacyCampaign);
+    Ok(legacy)
+  }
+
+  // Returns the lamports the distributor paid to keep the larger account rent exempt
+  fn resize_campaign(&self, space: usize) -> Result<u64> {
+    let campaign_info = self.campaign.to_account_info();
+    let lamports_before = campaign_info.lamports();
+    let required = Rent::get()?.minimum_balance(space);
+    if required > lamports_before {
+      system_program::transfer(
+        CpiContext::new(
+          self.system_program.to_account_info(),
+          system_program::Transfer {
+            from: self.distributor.to_account_info(),
+            to: campaign_info.clone(),
+          },
+        ),
+        required - lamports_before,
+      )?;
+    }
+    campaign_info.resize(space)?;
+    Ok(campaign_info.lamports() - lamports_before)
+  }
+
+  // Legacy leaves and nodes are hashed without prefixes and commit to the reward still stored
+  fn write_campaign(&self, legacy: LegacyCampaignState, realloc_rent: u64) -> Result<()> {
+    let participants = legacy.rewards.len();
+    let campaign = CampaignState {
+      launcher: legacy.launcher,
+      mint: legacy.mint,
+      distributor: self.distributor.key(),
+      fund: legacy.fund,
+      seed: legacy.seed,
+      start_ts: 0,
+      end_ts: i64::MAX,
+      claim_open_ts: 0,
+      claim_close_ts: 0,
+      vesting_cliff: 0,
+      vesting_duration: 0,
+      vesting_outstanding: 0,
+      realloc_rent,
+      locked: legacy.locked,
+      closed: 0,
+      hash_version: HASH_VERSION_LEGACY,
+      hash_algo: HASH_ALGO_SHA256,
+      merkle_root: legacy.merkle_root,
+      root_grace_slots: 0,
+      root_history_idx: 0,
+      root_history: Default::default(),
+      rewards: legacy.rewards,
+      claimed: vec![0u64; participants],
+    };
+    let mut data = self.campaign.try_borrow_mut_data()?;
+    campaign.try_serialize(&mut &mut data[..])
+  }
+}
+
+pub fn handler(ctx: Context<MigrateCampaign>) -> Result<()> {
+  let legacy = ctx.accounts.read_legacy()?;
+  let participants = legacy.rewards.len();
+  let realloc_rent = ctx.accounts.resize_campaign(CampaignState::space(participants))?;
+  ctx.accounts.write_campaign(legacy, realloc_rent)?;
+  emit!(MigrateEvent {
+    campaign: ctx.accounts.campaign.key(),
+    distributor: ctx.accounts.distributor.key(),
+    participants: participants as u64,
+  });
+  Ok(())
+}
@@ -9,4 +9,34 @@ pub mod claim;
 pub use claim::*;
 
 pub mod extend;
-pub use extend::*;
\ No newline at end of file
+pub use extend::*;
+
+pub mod config;
+pub use config::*;
+
+pub mod clawback;
+pub use clawback::*;
+
+pub mod close;
+pub use close::*;
+
+pub mod top_up;
+pub use top_up::*;
+
+pub mod withdraw;
+pub use withdraw::*;
+
+pub mod vesting;
+pub use vesting::*;
+
+pub mod relay;
+pub use relay::*;
+
+pub mod claim_many;
+pub use claim_many::*;
+
+pub mod fee;
+pub use fee::*;
+
+pub mod migrate;
+pub use migrate::*;
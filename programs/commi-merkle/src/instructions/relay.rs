@@ -0,0 +1,164 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{TokenInterface, TokenAccount, Mint};
+use crate::state::{CampaignState, Config, VestingState, PAUSE_CLAIM};
+use crate::errors::CommiError;
+use crate::events::ClaimEvent;
+
+// Layout of a single-signature Ed25519 program instruction
+const ED25519_HEADER_LEN: usize = 16;
+const ED25519_PUBKEY_LEN: usize = 32;
+const ED25519_SIGNATURE_LEN: usize = 64;
+
+#[derive(Accounts)]
+pub struct ClaimRelayed<'info> {
+  #[account(mut)]
+  pub payer: Signer<'info>,
+
+  pub claimer: SystemAccount<'info>,
+
+  #[account(mut)]
+  pub launcher: SystemAccount<'info>,
+
+  #[account(
+    seeds = [b"config"],
+    bump = config.bump,
+  )]
+  pub config: Account<'info, Config>,
+
+  #[account(
+    mut,
+    seeds = [b"campaign", launcher.key().as_ref(), mint.key().as_ref(), campaign.seed.to_le_bytes().as_ref()],
+    bump,
+    has_one = mint @ CommiError::InvalidMint,
+    has_one = launcher @ CommiError::InvalidLauncher
+  )]
+  pub campaign: Account<'info, CampaignState>,
+  pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+  #[account(
+    mut,
+    associated_token::mint = mint,
+    associated_token::authority = campaign,
+    associated_token::token_program = token_program
+  )]
+  pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+  #[account(
+    init_if_needed,
+    payer = payer,
+    associated_token::mint = mint,
+    associated_token::authority = claimer,
+    associated_token::token_program = token_program
+  )]
+  pub claimer_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+  // Required when the campaign vests rewards instead of paying them out
+  #[account(
+    init_if_needed,
+    payer = payer,
+    space = VestingState::space(),
+    seeds = [b"vesting", campaign.key().as_ref(), claimer.key().as_ref()],
+    bump,
+  )]
+  pub vesting: Option<Box<Account<'info, VestingState>>>,
+
+  /// CHECK: address is constrained to the instructions sysvar
+  #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+  pub instructions: UncheckedAccount<'info>,
+
+  pub associated_token_program: Program<'info, AssociatedToken>,
+  pub token_program: Interface<'info, TokenInterface>,
+  pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimRelayed<'info> {
+
+  // The claimer signs campaign || user_idx || nonce || claimed || claim_amount in an Ed25519 instruction placed
+  // right before this one. `claimed` grows with every claim at user_idx, so a signature can only be used once.
+  fn verify_authorization(&self, user_idx: u64, nonce: u64, claim_amount: u64) -> Result<()> {
+    let instructions = self.instructions.to_account_info();
+    let current_index = load_current_index_checked(&instructions)?;
+    require_gt!(current_index, 0, CommiError::InvalidRelaySignature);
+    let ix = load_instruction_at_checked(current_index as usize - 1, &instructions)?;
+    require_keys_eq!(ix.program_id, ed25519_program::ID, CommiError::InvalidRelaySignature);
+    require!(ix.accounts.is_empty(), CommiError::InvalidRelaySignature);
+
+    let data = &ix.data;
+    require_gte!(data.len(), ED25519_HEADER_LEN, CommiError::InvalidRelaySignature);
+    require_eq!(data[0], 1, CommiError::InvalidRelaySignature);
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]) as usize;
+    let signature_offset = read_u16(2);
+    let pubkey_offset = read_u16(6);
+    let message_offset = read_u16(10);
+    let message_size = read_u16(12);
+    // Every offset must point into this same instruction
+    for at in [4, 8, 14] {
+      require_eq!(read_u16(at), u16::MAX as usize, CommiError::InvalidRelaySignature);
+    }
+    require_gte!(data.len(), signature_offset + ED25519_SIGNATURE_LEN, CommiError::InvalidRelaySignature);
+    require_gte!(data.len(), pubkey_offset + ED25519_PUBKEY_LEN, CommiError::InvalidRelaySignature);
+    require_gte!(data.len(), message_offset + message_size, CommiError::InvalidRelaySignature);
+
+    let pubkey = &data[pubkey_offset..pubkey_offset + ED25519_PUBKEY_LEN];
+    require!(pubkey == self.claimer.key().as_ref(), CommiError::InvalidRelaySignature);
+
+    let expected = [
+      self.campaign.key().as_ref(),
+      user_idx.to_le_bytes().as_ref(),
+      nonce.to_le_bytes().as_ref(),
+      self.campaign.claimed[user_idx as usize].to_le_bytes().as_ref(),
+      claim_amount.to_le_bytes().as_ref(),
+    ].concat();
+    require!(&data[message_offset..message_offset + message_size] == expected.as_slice(), CommiError::InvalidRelaySignature);
+    Ok(())
+  }
+
+  fn vest_tokens(&mut self, amount: u64, bump: u8) -> Result<()> {
+    let claimer = self.claimer.key();
+    CampaignState::vest_tokens(&mut self.campaign, self.vesting.as_deref_mut(), claimer, amount, bump)
+  }
+
+  // Only paid-out claims need the claimer's token account, so vesting claims never create it
+  fn claim_tokens(&self, amount: u64, bump: u8) -> Result<()> {
+    let claimer_ata = self.claimer_ata.as_ref().ok_or(CommiError::InvalidRecipient)?;
+    CampaignState::transfer_from_vault(
+      &self.campaign,
+      bump,
+      self.vault.to_account_info(),
+      &self.mint,
+      claimer_ata.to_account_info(),
+      self.token_program.to_account_info(),
+      amount,
+    )
+  }
+}
+
+pub fn handler(ctx: Context<ClaimRelayed>, user_idx: u64, proof: Vec<[u8; 32]>, nonce: u64, claim_amount: u64) -> Result<()> {
+  require!(!ctx.accounts.config.is_paused(PAUSE_CLAIM), CommiError::ProgramPaused);
+  require_eq!(ctx.accounts.campaign.closed, 0, CommiError::CampaignClosed);
+  require_eq!(ctx.accounts.campaign.locked, 0, CommiError::CampaignLocked);
+  let clock = Clock::get()?;
+  ctx.accounts.campaign.verify_claim_window(clock.unix_timestamp)?;
+  ctx.accounts.campaign.verify_claim_status(&ctx.accounts.claimer.key(), user_idx, &proof, nonce, clock.slot)?;
+  ctx.accounts.verify_authorization(user_idx, nonce, claim_amount)?;
+  let amount = ctx.accounts.campaign.claimable_amount(user_idx, claim_amount)?;
+  let vested = ctx.accounts.campaign.is_vesting();
+  if vested {
+    ctx.accounts.vest_tokens(amount, ctx.bumps.vesting.unwrap_or_default())?;
+  } else {
+    ctx.accounts.claim_tokens(amount, ctx.bumps.campaign)?;
+  }
+  let remaining = ctx.accounts.campaign.take_reward(user_idx, amount);
+  emit!(ClaimEvent {
+    claimer: ctx.accounts.claimer.key(),
+    campaign: ctx.accounts.campaign.key(),
+    recipient: ctx.accounts.claimer.key(),
+    amount,
+    remaining,
+    vested,
+  });
+  Ok(())
+}
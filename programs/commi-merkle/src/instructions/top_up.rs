@@ -0,0 +1,86 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{TokenInterface, TokenAccount, Mint, transfer_checked, TransferChecked};
+use crate::state::{CampaignState, Config, PAUSE_LAUNCH};
+use crate::errors::CommiError;
+use crate::events::TopUpEvent;
+
+#[derive(Accounts)]
+pub struct TopUp<'info> {
+  #[account(mut)]
+  pub launcher: Signer<'info>,
+
+  #[account(
+    seeds = [b"config"],
+    bump = config.bump,
+  )]
+  pub config: Account<'info, Config>,
+
+  #[account(
+    mut,
+    seeds = [b"campaign", launcher.key().as_ref(), mint.key().as_ref(), campaign.seed.to_le_bytes().as_ref()],
+    bump,
+    has_one = mint @ CommiError::InvalidMint,
+    has_one = launcher @ CommiError::InvalidLauncher
+  )]
+  pub campaign: Account<'info, CampaignState>,
+  pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+  #[account(
+    mut,
+    associated_token::mint = mint,
+    associated_token::authority = launcher,
+    associated_token::token_program = token_program
+  )]
+  pub launcher_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+  #[account(
+    mut,
+    associated_token::mint = mint,
+    associated_token::authority = campaign,
+    associated_token::token_program = token_program
+  )]
+  pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+  pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> TopUp<'info> {
+
+  fn deposit_tokens(&self, amount: u64) -> Result<()> {
+    transfer_checked(
+      CpiContext::new(
+        self.token_program.to_account_info(),
+        TransferChecked {
+          from: self.launcher_ata.to_account_info(),
+          mint: self.mint.to_account_info(),
+          to: self.vault.to_account_info(),
+          authority: self.launcher.to_account_info(),
+        },
+      ),
+      amount,
+      self.mint.decimals
+    )?;
+    Ok(())
+  }
+
+  fn update_fund(&mut self, amount: u64) -> Result<()> {
+    self.campaign.fund = self.campaign.fund.checked_add(amount).ok_or(CommiError::InvalidFund)?;
+    self.campaign.rewards[0] = self.campaign.rewards[0].checked_add(amount).ok_or(CommiError::InvalidFund)?;
+    Ok(())
+  }
+}
+
+pub fn handler(ctx: Context<TopUp>, amount: u64) -> Result<()> {
+  require!(!ctx.accounts.config.is_paused(PAUSE_LAUNCH), CommiError::ProgramPaused);
+  require_eq!(ctx.accounts.campaign.closed, 0, CommiError::CampaignClosed);
+  require_gt!(ctx.accounts.campaign.end_ts, Clock::get()?.unix_timestamp, CommiError::CampaignEnded);
+  require_gt!(amount, 0, CommiError::InvalidFund);
+  ctx.accounts.deposit_tokens(amount)?;
+  ctx.accounts.update_fund(amount)?;
+  emit!(TopUpEvent {
+    campaign: ctx.accounts.campaign.key(),
+    amount,
+    fund: ctx.accounts.campaign.fund,
+  });
+  Ok(())
+}
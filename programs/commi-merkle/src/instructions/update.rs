@@ -1,6 +1,6 @@
-use anchor_lang::{prelude::*, system_program};
+use anchor_lang::prelude::*;
 use anchor_spl::token_interface::Mint;
-use crate::state::CampaignState;
+use crate::state::{CampaignState, Config, PAUSE_UPDATE};
 use crate::errors::CommiError;
 use crate::events::UpdateEvent;
 
@@ -9,6 +9,13 @@ pub struct Update<'info> {
   #[account(mut)]
   pub distributor: Signer<'info>,
 
+  #[account(
+    seeds = [b"config"],
+    bump = config.bump,
+    constraint = config.distributors.contains(&distributor.key()) @ CommiError::InvalidDistributor,
+  )]
+  pub config: Account<'info, Config>,
+
   #[account(mut)]
   pub launcher: SystemAccount<'info>,
 
@@ -17,7 +24,8 @@ pub struct Update<'info> {
     seeds = [b"campaign", launcher.key().as_ref(), mint.key().as_ref(), campaign.seed.to_le_bytes().as_ref()],
     bump,
     has_one = mint @ CommiError::InvalidMint,
-    has_one = launcher @ CommiError::InvalidLauncher
+    has_one = launcher @ CommiError::InvalidLauncher,
+    has_one = distributor @ CommiError::InvalidDistributor
   )]
   pub campaign: Account<'info, CampaignState>,
   pub mint: Box<InterfaceAccount<'info, Mint>>,
@@ -31,7 +39,7 @@ impl<'info> Update<'info> {
     Ok(())
   }
 
-  fn update(&mut self, root: [u8; 32], participants: Vec<[u64; 2]>) -> Result<()> {
+  fn update(&mut self, root: [u8; 32], participants: Vec<[u64; 2]>, slot: u64) -> Result<()> {
     let accumulative_rewards = participants
       .iter()
       .fold(0, |acc, participant| acc + participant[1]);
@@ -41,6 +49,9 @@ impl<'info> Update<'info> {
       self.campaign.rewards[participant[0] as usize] = 
         self.campaign.rewards[participant[0] as usize].checked_add(participant[1]).ok_or(CommiError::InvalidUpdateAmount)?;
     }
+    if self.campaign.merkle_root != [0u8; 32] {
+      self.campaign.push_root_history(slot);
+    }
     self.campaign.merkle_root = root;
     Ok(())
   }
@@ -51,10 +62,12 @@ impl<'info> Update<'info> {
   }
 }
 
-// TODO: Update distributor key to a valid fixed address
 pub fn handler(ctx: Context<Update>, root: [u8; 32], participants: Vec<[u64; 2]>) -> Result<()> {
-  require_eq!(ctx.accounts.distributor.key(), Pubkey::from_str_const("5PpeUwd8XqJ4y75gEM3ATrmaV4piR9GdZhpuFhH76UGw"), CommiError::InvalidDistributor);
-  ctx.accounts.update(root, participants)?;
+  require!(!ctx.accounts.config.is_paused(PAUSE_UPDATE), CommiError::ProgramPaused);
+  require_eq!(ctx.accounts.campaign.closed, 0, CommiError::CampaignClosed);
+  let clock = Clock::get()?;
+  require_gt!(ctx.accounts.campaign.end_ts, clock.unix_timestamp, CommiError::CampaignEnded);
+  ctx.accounts.update(root, participants, clock.slot)?;
   ctx.accounts.unlock()?;
   emit!(UpdateEvent {
     campaign: ctx.accounts.campaign.key(),
@@ -63,8 +76,8 @@ pub fn handler(ctx: Context<Update>, root: [u8; 32], participants: Vec<[u64; 2]>
   Ok(())
 }
 
+// Locking only halts claims, so it stays available while updates are paused
 pub fn lock(ctx: Context<Update>) -> Result<()> {
-  require_eq!(ctx.accounts.distributor.key(), Pubkey::from_str_const("5PpeUwd8XqJ4y75gEM3ATrmaV4piR9GdZhpuFhH76UGw"), CommiError::InvalidDistributor);
   ctx.accounts.lock()?;
   Ok(())
 }
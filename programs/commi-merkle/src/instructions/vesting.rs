@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{TokenInterface, TokenAccount, Mint};
+use crate::state::{CampaignState, Config, VestingState, PAUSE_CLAIM};
+use crate::errors::CommiError;
+use crate::events::WithdrawVestedEvent;
+
+#[derive(Accounts)]
+pub struct WithdrawVested<'info> {
+  #[account(mut)]
+  pub claimer: Signer<'info>,
+
+  pub launcher: SystemAccount<'info>,
+
+  #[account(
+    seeds = [b"config"],
+    bump = config.bump,
+  )]
+  pub config: Account<'info, Config>,
+
+  #[account(
+    mut,
+    seeds = [b"campaign", launcher.key().as_ref(), mint.key().as_ref(), campaign.seed.to_le_bytes().as_ref()],
+    bump,
+    has_one = mint @ CommiError::InvalidMint,
+    has_one = launcher @ CommiError::InvalidLauncher
+  )]
+  pub campaign: Account<'info, CampaignState>,
+  pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+  #[account(
+    mut,
+    seeds = [b"vesting", campaign.key().as_ref(), claimer.key().as_ref()],
+    bump = vesting.bump,
+    has_one = campaign,
+    has_one = claimer,
+  )]
+  pub vesting: Account<'info, VestingState>,
+
+  #[account(
+    mut,
+    associated_token::mint = mint,
+    associated_token::authority = campaign,
+    associated_token::token_program = token_program
+  )]
+  pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+  #[account(
+    init_if_needed,
+    payer = claimer,
+    associated_token::mint = mint,
+    associated_token::authority = claimer,
+    associated_token::token_program = token_program
+  )]
+  pub claimer_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+  pub associated_token_program: Program<'info, AssociatedToken>,
+  pub token_program: Interface<'info, TokenInterface>,
+  pub system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawVested<'info> {
+
+  fn update_status(&mut self, now: i64) -> Result<u64> {
+    let unlocked = self.campaign.vested_amount(self.vesting.total, now);
+    let amount = unlocked.saturating_sub(self.vesting.withdrawn);
+    require_gt!(amount, 0, CommiError::NothingVested);
+    self.vesting.withdrawn = self.vesting.withdrawn.checked_add(amount).ok_or(CommiError::InvalidClaimAmount)?;
+    self.campaign.vesting_outstanding = self.campaign.vesting_outstanding
+      .checked_sub(amount).ok_or(CommiError::InvalidClaimAmount)?;
+    Ok(amount)
+  }
+
+  fn withdraw_tokens(&self, amount: u64, bump: u8) -> Result<()> {
+    CampaignState::transfer_from_vault(
+      &self.campaign,
+      bump,
+      self.vault.to_account_info(),
+      &self.mint,
+      self.claimer_ata.to_account_info(),
+      self.token_program.to_account_info(),
+      amount,
+    )
+  }
+}
+
+pub fn handler(ctx: Context<WithdrawVested>) -> Result<()> {
+  require!(!ctx.accounts.config.is_paused(PAUSE_CLAIM), CommiError::ProgramPaused);
+  let amount = ctx.accounts.update_status(Clock::get()?.unix_timestamp)?;
+  ctx.accounts.withdraw_tokens(amount, ctx.bumps.campaign)?;
+  emit!(WithdrawVestedEvent {
+    claimer: ctx.accounts.claimer.key(),
+    campaign: ctx.accounts.campaign.key(),
+    amount,
+    remaining: ctx.accounts.vesting.total - ctx.accounts.vesting.withdrawn,
+  });
+  Ok(())
+}
@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{TokenInterface, TokenAccount, Mint};
+use crate::state::{CampaignState, Config, PAUSE_WITHDRAW};
+use crate::errors::CommiError;
+use crate::events::WithdrawEvent;
+
+#[derive(Accounts)]
+pub struct WithdrawUnallocated<'info> {
+  #[account(mut)]
+  pub launcher: Signer<'info>,
+
+  #[account(
+    seeds = [b"config"],
+    bump = config.bump,
+  )]
+  pub config: Account<'info, Config>,
+
+  #[account(
+    mut,
+    seeds = [b"campaign", launcher.key().as_ref(), mint.key().as_ref(), campaign.seed.to_le_bytes().as_ref()],
+    bump,
+    has_one = mint @ CommiError::InvalidMint,
+    has_one = launcher @ CommiError::InvalidLauncher
+  )]
+  pub campaign: Account<'info, CampaignState>,
+  pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+  #[account(
+    mut,
+    associated_token::mint = mint,
+    associated_token::authority = campaign,
+    associated_token::token_program = token_program
+  )]
+  pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+  #[account(
+    mut,
+    associated_token::mint = mint,
+    associated_token::authority = launcher,
+    associated_token::token_program = token_program
+  )]
+  pub launcher_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+  pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> WithdrawUnallocated<'info> {
+
+  fn update_fund(&mut self, amount: u64) -> Result<()> {
+    self.campaign.rewards[0] = self.campaign.rewards[0].checked_sub(amount).ok_or(CommiError::InsufficientBalance)?;
+    self.campaign.fund = self.campaign.fund.checked_sub(amount).ok_or(CommiError::InsufficientBalance)?;
+    Ok(())
+  }
+
+  fn withdraw_tokens(&self, amount: u64, bump: u8) -> Result<()> {
+    CampaignState::transfer_from_vault(
+      &self.campaign,
+      bump,
+      self.vault.to_account_info(),
+      &self.mint,
+      self.launcher_ata.to_account_info(),
+      self.token_program.to_account_info(),
+      amount,
+    )
+  }
+}
+
+pub fn handler(ctx: Context<WithdrawUnallocated>, amount: u64) -> Result<()> {
+  require!(!ctx.accounts.config.is_paused(PAUSE_WITHDRAW), CommiError::ProgramPaused);
+  require_eq!(ctx.accounts.campaign.closed, 0, CommiError::CampaignClosed);
+  // An unlocked, running campaign may have an update in flight that allocates from rewards[0]
+  require!(
+    ctx.accounts.campaign.locked == 1 || Clock::get()?.unix_timestamp >= ctx.accounts.campaign.end_ts,
+    CommiError::CampaignNotLocked
+  );
+  require_gt!(amount, 0, CommiError::InvalidFund);
+  ctx.accounts.update_fund(amount)?;
+  ctx.accounts.withdraw_tokens(amount, ctx.bumps.campaign)?;
+  emit!(WithdrawEvent {
+    campaign: ctx.accounts.campaign.key(),
+    amount,
+    fund: ctx.accounts.campaign.fund,
+  });
+  Ok(())
+}
@@ -3,39 +3,164 @@ use anchor_lang::prelude::*;
 mod errors;
 mod events;
 mod instructions;
-mod state;
+pub mod state;
 
 use instructions::*;
+use state::CampaignSchedule;
 
 declare_id!("4BY7rXDgtbkgjAY1acpy3Pfg7hXhZf1vpFNtfVreSJHL");
 
-#[program]
-pub mod commi_merkle {
+// The IDL instructions generated by `#[program]` still call the deprecated `AccountInfo::realloc`,
+// so the allow is kept to the module holding the generated code.
+#[allow(deprecated)]
+mod entrypoint {
     use super::*;
 
-    #[instruction(discriminator = 0)]
-    pub fn launch(ctx: Context<Launch>, seed: u64, fund: u64) -> Result<()> {
-        instructions::launch::handler(ctx, seed, fund)
-    }
+    #[program]
+    pub mod commi_merkle {
+        use super::*;
 
-    #[instruction(discriminator = 1)]
-    pub fn update(ctx: Context<Update>, root: [u8; 32], participants: Vec<[u64; 2]>) -> Result<()> {
-        instructions::update::handler(ctx, root, participants)
-    }
+        #[instruction(discriminator = 0)]
+        pub fn launch(ctx: Context<Launch>, seed: u64, fund: u64, schedule: CampaignSchedule, hash_algo: u8) -> Result<()> {
+            instructions::launch::handler(ctx, seed, fund, schedule, hash_algo)
+        }
 
-    #[instruction(discriminator = 2)]
-    pub fn claim(ctx: Context<Claim>, user_idx: u64, proof: Vec<[u8; 32]>, nonce: u64) -> Result<()> {
-        instructions::claim::handler(ctx, user_idx, proof, nonce)
-    }
+        #[instruction(discriminator = 1)]
+        pub fn update(ctx: Context<Update>, root: [u8; 32], participants: Vec<[u64; 2]>) -> Result<()> {
+            instructions::update::handler(ctx, root, participants)
+        }
 
-    #[instruction(discriminator = 3)]
-    pub fn extend(ctx: Context<Extend>, new_participants: u64) -> Result<()> {
-        instructions::extend::handler(ctx, new_participants)
-    }
+        #[instruction(discriminator = 2)]
+        pub fn claim(ctx: Context<Claim>, user_idx: u64, proof: Vec<[u8; 32]>, nonce: u64, claim_amount: u64) -> Result<()> {
+            instructions::claim::handler(ctx, user_idx, proof, nonce, claim_amount)
+        }
+
+        #[instruction(discriminator = 3)]
+        pub fn extend(ctx: Context<Extend>, new_participants: u64) -> Result<()> {
+            instructions::extend::handler(ctx, new_participants)
+        }
+
+        #[instruction(discriminator = 4)]
+        pub fn lock(ctx: Context<Update>) -> Result<()> {
+            instructions::update::lock(ctx)
+        }
+
+        #[instruction(discriminator = 5)]
+        pub fn initialize_config(ctx: Context<InitializeConfig>, distributor: Pubkey, price_feed: Pubkey) -> Result<()> {
+            instructions::config::initialize(ctx, distributor, price_feed)
+        }
+
+        #[instruction(discriminator = 6)]
+        pub fn propose_admin(ctx: Context<UpdateConfig>, pending_admin: Pubkey) -> Result<()> {
+            instructions::config::propose_admin(ctx, pending_admin)
+        }
+
+        #[instruction(discriminator = 7)]
+        pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+            instructions::config::accept_admin(ctx)
+        }
+
+        #[instruction(discriminator = 8)]
+        pub fn propose_distributor(ctx: Context<UpdateConfig>, pending_distributor: Pubkey) -> Result<()> {
+            instructions::config::propose_distributor(ctx, pending_distributor)
+        }
+
+        #[instruction(discriminator = 9)]
+        pub fn accept_distributor(ctx: Context<AcceptDistributor>) -> Result<()> {
+            instructions::config::accept_distributor(ctx)
+        }
+
+        #[instruction(discriminator = 10)]
+        pub fn remove_distributor(ctx: Context<UpdateConfig>, distributor: Pubkey) -> Result<()> {
+            instructions::config::remove_distributor(ctx, distributor)
+        }
+
+        #[instruction(discriminator = 11)]
+        pub fn assign_distributor(ctx: Context<AssignDistributor>) -> Result<()> {
+            instructions::config::assign_distributor(ctx)
+        }
+
+        #[instruction(discriminator = 12)]
+        pub fn pause(ctx: Context<UpdateConfig>, flags: u8) -> Result<()> {
+            instructions::config::pause(ctx, flags)
+        }
+
+        #[instruction(discriminator = 13)]
+        pub fn unpause(ctx: Context<UpdateConfig>, flags: u8) -> Result<()> {
+            instructions::config::unpause(ctx, flags)
+        }
+
+        #[instruction(discriminator = 14)]
+        pub fn clawback(ctx: Context<Clawback>) -> Result<()> {
+            instructions::clawback::handler(ctx)
+        }
+
+        #[instruction(discriminator = 15)]
+        pub fn close_campaign(ctx: Context<CloseCampaign>) -> Result<()> {
+            instructions::close::handler(ctx)
+        }
+
+        #[instruction(discriminator = 16)]
+        pub fn top_up(ctx: Context<TopUp>, amount: u64) -> Result<()> {
+            instructions::top_up::handler(ctx, amount)
+        }
+
+        #[instruction(discriminator = 17)]
+        pub fn withdraw_unallocated(ctx: Context<WithdrawUnallocated>, amount: u64) -> Result<()> {
+            instructions::withdraw::handler(ctx, amount)
+        }
+
+        #[instruction(discriminator = 18)]
+        pub fn withdraw_vested(ctx: Context<WithdrawVested>) -> Result<()> {
+            instructions::vesting::handler(ctx)
+        }
+
+        #[instruction(discriminator = 19)]
+        pub fn claim_relayed(ctx: Context<ClaimRelayed>, user_idx: u64, proof: Vec<[u8; 32]>, nonce: u64, claim_amount: u64) -> Result<()> {
+            instructions::relay::handler(ctx, user_idx, proof, nonce, claim_amount)
+        }
+
+        #[instruction(discriminator = 20)]
+        pub fn claim_many<'info>(ctx: Context<'_, '_, 'info, 'info, ClaimMany<'info>>, claims: Vec<ClaimArgs>) -> Result<()> {
+            instructions::claim_many::handler(ctx, claims)
+        }
+
+        #[instruction(discriminator = 21)]
+        pub fn update_fee_config(
+            ctx: Context<UpdateFeeConfig>,
+            fee_receiver: Pubkey,
+            fee_usd_cents: u64,
+            waivers: Vec<Pubkey>,
+            fee_mints: Vec<Pubkey>,
+        ) -> Result<()> {
+            instructions::fee::update_fee_config(ctx, fee_receiver, fee_usd_cents, waivers, fee_mints)
+        }
+
+        #[instruction(discriminator = 22)]
+        pub fn quote_fee(ctx: Context<QuoteFee>, launcher: Pubkey) -> Result<u64> {
+            instructions::fee::quote_fee(ctx, launcher)
+        }
+
+        #[instruction(discriminator = 23)]
+        pub fn set_oracle_policy(ctx: Context<UpdateConfig>, max_conf_bps: u16, min_verification: u8) -> Result<()> {
+            instructions::config::set_oracle_policy(ctx, max_conf_bps, min_verification)
+        }
+
+        #[instruction(discriminator = 24)]
+        pub fn set_fallback_feed(ctx: Context<UpdateConfig>, fallback_feed: Pubkey, fallback_kind: u8) -> Result<()> {
+            instructions::config::set_fallback_feed(ctx, fallback_feed, fallback_kind)
+        }
+
+        #[instruction(discriminator = 25)]
+        pub fn migrate_campaign(ctx: Context<MigrateCampaign>) -> Result<()> {
+            instructions::migrate::handler(ctx)
+        }
 
-    #[instruction(discriminator = 4)]
-    pub fn lock(ctx: Context<Update>) -> Result<()> {
-        instructions::update::lock(ctx)
+        #[instruction(discriminator = 26)]
+        pub fn set_price_feed(ctx: Context<UpdateConfig>, price_feed: Pubkey) -> Result<()> {
+            instructions::config::set_price_feed(ctx, price_feed)
+        }
     }
 }
 
+pub use entrypoint::*;
@@ -1,12 +1,410 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{close_account, transfer_checked, CloseAccount, Mint, TransferChecked};
+use commi_merkle_tree::LeafHasher;
+use pyth_solana_receiver_sdk::price_update::VerificationLevel;
+use crate::errors::CommiError;
 
 #[account(discriminator = 1)]
 pub struct CampaignState {
   pub launcher: Pubkey,
   pub mint: Pubkey,
+  pub distributor: Pubkey,
   pub fund: u64,
   pub seed: u64,
+  pub start_ts: i64,
+  pub end_ts: i64,
+  pub claim_open_ts: i64,
+  pub claim_close_ts: i64,
+  pub vesting_cliff: i64,
+  pub vesting_duration: i64,
+  pub vesting_outstanding: u64,
+  // Rent paid by distributors for `extend`, refunded on close to the distributor assigned at that time
+  pub realloc_rent: u64,
   pub locked: u8, 
+  pub closed: u8,
+  pub hash_version: u8,
+  pub hash_algo: u8,
   pub merkle_root: [u8; 32],
+  pub root_grace_slots: u64,
+  pub root_history_idx: u8,
+  pub root_history: [RootEntry; ROOT_HISTORY_LEN],
+  // Cumulative allocation per index, the amount its leaf commits to; rewards[0] is the unallocated pool
   pub rewards: Vec<u64>,
+  // Amount taken so far per index, so one proof keeps working across partial claims
+  pub claimed: Vec<u64>,
+}
+
+impl CampaignState {
+  pub fn space(participants: usize) -> usize {
+    32 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 1 + 1 + 1 + 32 + 8 + 1 + ROOT_HISTORY_LEN * (32 + 8) + 24 + participants * 8 + 4 + participants * 8 + CampaignState::DISCRIMINATOR.len()
+  }
+
+  // Keeps the replaced root so proofs fetched just before an update stay valid for a while
+  pub fn push_root_history(&mut self, slot: u64) {
+    let idx = self.root_history_idx as usize % ROOT_HISTORY_LEN;
+    self.root_history[idx] = RootEntry { root: self.merkle_root, slot };
+    self.root_history_idx = ((idx + 1) % ROOT_HISTORY_LEN) as u8;
+  }
+
+  pub fn is_known_root(&self, root: &[u8; 32], slot: u64) -> bool {
+    if *root == self.merkle_root {
+      return true;
+    }
+    self.root_history.iter().any(|entry| {
+      entry.root != [0u8; 32]
+        && entry.root == *root
+        && slot.saturating_sub(entry.slot) < self.root_grace_slots
+    })
+  }
+
+  pub fn outstanding(&self, user_idx: usize) -> u64 {
+    self.rewards[user_idx].saturating_sub(self.claimed[user_idx])
+  }
+
+  pub fn is_vesting(&self) -> bool {
+    self.vesting_duration > 0
+  }
+
+  // Linear unlock from start_ts, nothing before the cliff
+  pub fn vested_amount(&self, total: u64, now: i64) -> u64 {
+    let elapsed = now.saturating_sub(self.start_ts);
+    if elapsed < self.vesting_cliff {
+      return 0;
+    }
+    if elapsed >= self.vesting_duration {
+      return total;
+    }
+    (total as u128 * elapsed as u128 / self.vesting_duration as u128) as u64
+  }
+
+  fn leaf_hasher(&self) -> LeafHasher {
+    LeafHasher::new(self.hash_algo, self.hash_version)
+  }
+
+  pub(crate) fn verify_claim_window(&self, now: i64) -> Result<()> {
+    require_gte!(now, self.claim_open_ts, CommiError::ClaimWindowNotOpen);
+    if self.claim_close_ts != 0 {
+      require_gt!(self.claim_close_ts, now, CommiError::ClaimWindowClosed);
+    }
+    Ok(())
+  }
+
+  // The leaf commits to the cumulative allocation, so an older root only verifies until an update adds to it
+  pub(crate) fn verify_claim_status(&self, claimer: &Pubkey, user_idx: u64, proof: &[[u8; 32]], nonce: u64, slot: u64) -> Result<()> {
+    // rewards[0] is the launcher's unallocated pool, not a claimable allocation
+    require_gt!(user_idx, 0, CommiError::InvalidUserIdx);
+    require_gt!(self.rewards.len(), user_idx as usize, CommiError::InvalidUserIdx);
+    require_gt!(self.outstanding(user_idx as usize), 0, CommiError::InvalidClaimAmount);
+    let hasher = self.leaf_hasher();
+    let leaf = hasher.leaf(&claimer.to_bytes(), self.rewards[user_idx as usize], user_idx, nonce);
+    let root = hasher.root_from_proof(leaf, user_idx, proof);
+    if !self.is_known_root(&root, slot) {
+      return err!(CommiError::InvalidProof);
+    }
+    Ok(())
+  }
+
+  // Claims are capped at the unclaimed allocation, so callers may pass u64::MAX to take everything
+  pub(crate) fn claimable_amount(&self, user_idx: u64, claim_amount: u64) -> Result<u64> {
+    let amount = claim_amount.min(self.outstanding(user_idx as usize));
+    require_gt!(amount, 0, CommiError::InvalidClaimAmount);
+    Ok(amount)
+  }
+
+  // Books a claim to the claimer's vesting account instead of paying it out
+  pub(crate) fn vest_tokens<'info>(
+    campaign: &mut Account<'info, CampaignState>,
+    vesting: Option<&mut Account<'info, VestingState>>,
+    claimer: Pubkey,
+    amount: u64,
+    bump: u8,
+  ) -> Result<()> {
+    let vesting = vesting.ok_or(CommiError::VestingAccountRequired)?;
+    if vesting.campaign == Pubkey::default() {
+      vesting.campaign = campaign.key();
+      vesting.claimer = claimer;
+      vesting.bump = bump;
+    }
+    vesting.total = vesting.total.checked_add(amount).ok_or(CommiError::InvalidClaimAmount)?;
+    campaign.vesting_outstanding = campaign.vesting_outstanding
+      .checked_add(amount).ok_or(CommiError::InvalidClaimAmount)?;
+    Ok(())
+  }
+
+  // Returns the balance left at user_idx, still claimable with the same proof
+  pub(crate) fn take_reward(&mut self, user_idx: u64, amount: u64) -> u64 {
+    self.claimed[user_idx as usize] += amount;
+    self.outstanding(user_idx as usize)
+  }
+
+  // Seeds the campaign PDA signs vault CPIs with
+  fn signer_seeds<'a>(&'a self, seed: &'a [u8; 8], bump: &'a [u8; 1]) -> [&'a [u8]; 5] {
+    [b"campaign", self.launcher.as_ref(), self.mint.as_ref(), seed, bump]
+  }
+
+  // Moves tokens out of the campaign vault, signed by the campaign PDA
+  pub(crate) fn transfer_from_vault<'info>(
+    campaign: &Account<'info, CampaignState>,
+    bump: u8,
+    vault: AccountInfo<'info>,
+    mint: &InterfaceAccount<'info, Mint>,
+    to: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    amount: u64,
+  ) -> Result<()> {
+    let (seed, bump) = (campaign.seed.to_le_bytes(), [bump]);
+    let signer_seeds = [&campaign.signer_seeds(&seed, &bump)[..]];
+
+    transfer_checked(
+      CpiContext::new_with_signer(
+        token_program,
+        TransferChecked {
+          from: vault,
+          mint: mint.to_account_info(),
+          to,
+          authority: campaign.to_account_info(),
+        },
+        &signer_seeds
+      ),
+      amount,
+      mint.decimals
+    )
+  }
+
+  // Closes the empty campaign vault, sending its rent to `destination`
+  pub(crate) fn close_vault<'info>(
+    campaign: &Account<'info, CampaignState>,
+    bump: u8,
+    vault: AccountInfo<'info>,
+    destination: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+  ) -> Result<()> {
+    let (seed, bump) = (campaign.seed.to_le_bytes(), [bump]);
+    let signer_seeds = [&campaign.signer_seeds(&seed, &bump)[..]];
+
+    close_account(
+      CpiContext::new_with_signer(
+        token_program,
+        CloseAccount {
+          account: vault,
+          destination,
+          authority: campaign.to_account_info(),
+        },
+        &signer_seeds
+      )
+    )
+  }
+}
+
+// Leaf encoding and hashing are shared with the off-chain tree builder
+pub use commi_merkle_tree::{HASH_ALGO_KECCAK256, HASH_ALGO_SHA256, HASH_VERSION_LEGACY, HASH_VERSION_PREFIXED};
+
+pub const ROOT_HISTORY_LEN: usize = 4;
+// About an hour of 400ms slots, long enough for proofs fetched before an update to land
+pub const MAX_ROOT_GRACE_SLOTS: u64 = 9_000;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct RootEntry {
+  pub root: [u8; 32],
+  // Slot at which the root was replaced by a newer one
+  pub slot: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CampaignSchedule {
+  pub start_ts: i64,
+  pub end_ts: i64,
+  pub claim_open_ts: i64,
+  // 0 keeps claims open until the campaign is clawed back
+  pub claim_close_ts: i64,
+  // Seconds after start_ts; a zero duration pays claims out immediately
+  pub vesting_cliff: i64,
+  pub vesting_duration: i64,
+  // Slots a replaced root keeps verifying claims, at most MAX_ROOT_GRACE_SLOTS; 0 only accepts the current root
+  pub root_grace_slots: u64,
+}
+
+impl CampaignSchedule {
+  pub fn is_valid(&self, now: i64) -> bool {
+    self.end_ts > now
+      && self.start_ts < self.end_ts
+      && self.claim_open_ts >= self.start_ts
+      && (self.claim_close_ts == 0 || self.claim_close_ts > self.claim_open_ts)
+      && self.vesting_cliff >= 0
+      && self.vesting_cliff <= self.vesting_duration
+      && (self.vesting_duration == 0 || self.start_ts > 0)
+      && self.root_grace_slots <= MAX_ROOT_GRACE_SLOTS
+  }
+}
+
+pub const MAX_DISTRIBUTORS: usize = 8;
+
+pub const PAUSE_LAUNCH: u8 = 1 << 0;
+pub const PAUSE_UPDATE: u8 = 1 << 1;
+pub const PAUSE_CLAIM: u8 = 1 << 2;
+// Launcher withdrawals out of a campaign vault
+pub const PAUSE_WITHDRAW: u8 = 1 << 3;
+pub const PAUSE_ALL: u8 = PAUSE_LAUNCH | PAUSE_UPDATE | PAUSE_CLAIM | PAUSE_WITHDRAW;
+
+// Oracle policy: confidence interval as a share of the price, in basis points
+pub const MAX_CONF_BPS: u16 = 10_000;
+pub const DEFAULT_MAX_CONF_BPS: u16 = 100;
+// `min_verification` is a guardian signature count for partially verified updates,
+// or MIN_VERIFICATION_FULL to only accept fully verified ones
+pub const MIN_VERIFICATION_FULL: u8 = u8::MAX;
+
+// Layout of `Config::fallback_feed`, read when the primary pull feed is stale
+pub const FALLBACK_PULL: u8 = 0;
+pub const FALLBACK_PUSH: u8 = 1;
+
+#[account(discriminator = 2)]
+pub struct Config {
+  pub admin: Pubkey,
+  pub pending_admin: Pubkey,
+  pub pending_distributor: Pubkey,
+  pub price_feed: Pubkey,
+  pub fallback_feed: Pubkey,
+  pub bump: u8,
+  pub paused: u8,
+  pub max_conf_bps: u16,
+  pub min_verification: u8,
+  pub fallback_kind: u8,
+  pub distributors: Vec<Pubkey>,
+}
+
+impl Config {
+  pub fn is_paused(&self, flag: u8) -> bool {
+    self.paused & flag != 0
+  }
+
+  pub fn min_verification_level(&self) -> VerificationLevel {
+    match self.min_verification {
+      MIN_VERIFICATION_FULL => VerificationLevel::Full,
+      num_signatures => VerificationLevel::Partial { num_signatures },
+    }
+  }
+
+  // conf / price <= max_conf_bps / 10_000, for a positive price
+  pub fn is_confident(&self, price: i64, conf: u64) -> bool {
+    (conf as u128) * (MAX_CONF_BPS as u128) <= (price as u128) * (self.max_conf_bps as u128)
+  }
+}
+
+pub const MAX_FEE_WAIVERS: usize = 16;
+pub const MAX_FEE_MINTS: usize = 4;
+
+#[account(discriminator = 4)]
+pub struct FeeConfig {
+  pub fee_receiver: Pubkey,
+  // USD fee charged per launch, in cents
+  pub fee_usd_cents: u64,
+  pub bump: u8,
+  pub waivers: Vec<Pubkey>,
+  // USD stablecoins accepted 1:1 for the fee instead of SOL
+  pub fee_mints: Vec<Pubkey>,
+}
+
+impl FeeConfig {
+  pub fn is_waived(&self, launcher: &Pubkey) -> bool {
+    self.waivers.contains(launcher)
+  }
+
+  pub fn accepts_mint(&self, mint: &Pubkey) -> bool {
+    self.fee_mints.contains(mint)
+  }
+
+  // Base units of a USD stablecoin with `decimals` worth `fee_usd_cents`, rounded up
+  pub fn fee_tokens(&self, decimals: u8) -> Option<u64> {
+    let scale = 10u128.checked_pow(decimals as u32)?;
+    u64::try_from((self.fee_usd_cents as u128).checked_mul(scale)?.div_ceil(100)).ok()
+  }
+
+  // Lamports worth `fee_usd_cents` at a SOL/USD price of `price * 10^exponent`,
+  // rounded up so the protocol never undercharges. None if the fee does not fit a u64.
+  pub fn fee_lamports(&self, price: i64, exponent: i32) -> Option<u64> {
+    let scale = 10u128.checked_pow(exponent.unsigned_abs())?;
+    // cents * 10^7 == USD * 10^9 lamports per SOL
+    let numerator = (self.fee_usd_cents as u128).checked_mul(10_000_000)?;
+    let (numerator, denominator) = if exponent < 0 {
+      (numerator.checked_mul(scale)?, price.unsigned_abs() as u128)
+    } else {
+      (numerator, (price.unsigned_abs() as u128).checked_mul(scale)?)
+    };
+    if denominator == 0 {
+      return None;
+    }
+    u64::try_from(numerator.div_ceil(denominator)).ok()
+  }
+}
+
+#[account(discriminator = 3)]
+pub struct VestingState {
+  pub campaign: Pubkey,
+  pub claimer: Pubkey,
+  pub total: u64,
+  pub withdrawn: u64,
+  pub bump: u8,
+}
+
+impl VestingState {
+  pub fn space() -> usize {
+    32 + 32 + 8 + 8 + 1 + VestingState::DISCRIMINATOR.len()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn fee_config() -> FeeConfig {
+    FeeConfig {
+      fee_receiver: Pubkey::new_unique(),
+      fee_usd_cents: 500,
+      bump: 255,
+      waivers: vec![],
+      fee_mints: vec![],
+    }
+  }
+
+  #[test]
+  fn fee_lamports_rounds_up_for_any_exponent() {
+    let fee_config = fee_config();
+    // $5 at $200.00000000 is exactly 0.025 SOL
+    assert_eq!(fee_config.fee_lamports(20_000_000_000, -8), Some(25_000_000));
+    // $5 at $3 is 1.666... SOL, charged up to the next lamport
+    assert_eq!(fee_config.fee_lamports(3, 0), Some(1_666_666_667));
+    assert_eq!(fee_config.fee_lamports(3, 2), Some(16_666_667));
+    assert_eq!(fee_config.fee_lamports(0, -8), None);
+    assert_eq!(fee_config.fee_lamports(1, -40), None);
+  }
+  #[test]
+  fn fee_tokens_scale_to_mint_decimals() {
+    let mut fee_config = fee_config();
+    assert_eq!(fee_config.fee_tokens(6), Some(5_000_000));
+    assert_eq!(fee_config.fee_tokens(0), Some(5));
+    fee_config.fee_usd_cents = 1;
+    assert_eq!(fee_config.fee_tokens(0), Some(1));
+    assert_eq!(fee_config.fee_tokens(40), None);
+  }
+
+  #[test]
+  fn oracle_policy_bounds_confidence_and_verification() {
+    let config = Config {
+      admin: Pubkey::new_unique(),
+      pending_admin: Pubkey::default(),
+      pending_distributor: Pubkey::default(),
+      price_feed: Pubkey::new_unique(),
+      fallback_feed: Pubkey::default(),
+      bump: 255,
+      paused: 0,
+      max_conf_bps: 100,
+      min_verification: 5,
+      fallback_kind: 0,
+      distributors: vec![],
+    };
+    assert!(config.is_confident(20_000_000_000, 200_000_000));
+    assert!(!config.is_confident(20_000_000_000, 200_000_001));
+    assert!(VerificationLevel::Partial { num_signatures: 5 }.gte(config.min_verification_level()));
+    assert!(!VerificationLevel::Partial { num_signatures: 4 }.gte(config.min_verification_level()));
+  }
 }
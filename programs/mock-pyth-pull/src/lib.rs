@@ -5,54 +5,72 @@ use pyth_solana_receiver_sdk::price_update::{
 
 declare_id!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
 
-#[program]
-pub mod mock_pyth_pull {
+// The IDL instructions generated by `#[program]` still call the deprecated `AccountInfo::realloc`,
+// so the allow is kept to the module holding the generated code.
+#[allow(deprecated)]
+mod entrypoint {
     use super::*;
 
-    pub fn initialize(
-        ctx: Context<Initialize>,
-        feed_id: String,
-        price: i64,
-        conf: u64,
-        expo: i32,
-    ) -> Result<()> {
-        let clock = Clock::get()?;
-
-        *ctx.accounts.price = PriceUpdateV2 {
-            write_authority: Pubkey::default(),
-            verification_level: VerificationLevel::Full,
-            price_message: PriceFeedMessage {
-                feed_id: get_feed_id_from_hex(&feed_id)?,
-                price: price,
-                conf: conf,
-                exponent: expo,
-                publish_time: clock.unix_timestamp,
-                prev_publish_time: 0,
-                ema_price: price,
-                ema_conf: conf,
-            },
-            posted_slot: clock.slot,
-        };
-
-        Ok(())
-    }
+    #[program]
+    pub mod mock_pyth_pull {
+        use super::*;
+
+        pub fn initialize(
+            ctx: Context<Initialize>,
+            feed_id: String,
+            price: i64,
+            conf: u64,
+            expo: i32,
+        ) -> Result<()> {
+            let clock = Clock::get()?;
+
+            *ctx.accounts.price = PriceUpdateV2 {
+                write_authority: Pubkey::default(),
+                verification_level: VerificationLevel::Full,
+                price_message: PriceFeedMessage {
+                    feed_id: get_feed_id_from_hex(&feed_id)?,
+                    price,
+                    conf,
+                    exponent: expo,
+                    publish_time: clock.unix_timestamp,
+                    prev_publish_time: 0,
+                    ema_price: price,
+                    ema_conf: conf,
+                },
+                posted_slot: clock.slot,
+            };
+
+            Ok(())
+        }
 
-    pub fn set_price(ctx: Context<SetPrice>, price: i64, conf: u64) -> Result<()> {
-        let clock = Clock::get()?;
+        pub fn set_price(ctx: Context<SetPrice>, price: i64, conf: u64) -> Result<()> {
+            let clock = Clock::get()?;
 
-        ctx.accounts.price.price_message.prev_publish_time =
-            ctx.accounts.price.price_message.publish_time;
-        ctx.accounts.price.price_message.publish_time = clock.unix_timestamp;
-        ctx.accounts.price.price_message.price = price;
-        ctx.accounts.price.price_message.conf = conf;
-        ctx.accounts.price.price_message.ema_price = price;
-        ctx.accounts.price.price_message.ema_conf = conf;
-        ctx.accounts.price.posted_slot = clock.slot;
+            ctx.accounts.price.price_message.prev_publish_time =
+                ctx.accounts.price.price_message.publish_time;
+            ctx.accounts.price.price_message.publish_time = clock.unix_timestamp;
+            ctx.accounts.price.price_message.price = price;
+            ctx.accounts.price.price_message.conf = conf;
+            ctx.accounts.price.price_message.ema_price = price;
+            ctx.accounts.price.price_message.ema_conf = conf;
+            ctx.accounts.price.posted_slot = clock.slot;
 
-        Ok(())
+            Ok(())
+        }
+
+        // Backdates the feed so tests can make it stale without waiting
+        pub fn set_publish_time(ctx: Context<SetPrice>, publish_time: i64) -> Result<()> {
+            ctx.accounts.price.price_message.prev_publish_time =
+                ctx.accounts.price.price_message.publish_time;
+            ctx.accounts.price.price_message.publish_time = publish_time;
+
+            Ok(())
+        }
     }
 }
 
+pub use entrypoint::*;
+
 #[derive(Accounts)]
 pub struct Initialize<'info> {
     #[account(mut)]
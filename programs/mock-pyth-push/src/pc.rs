@@ -9,34 +9,24 @@ pub struct AccKey {
     pub val: [u8; 32],
 }
 
-#[derive(Copy, Clone)]
+#[derive(Default, Copy, Clone)]
 #[repr(C)]
 #[allow(dead_code)]
 pub enum PriceStatus {
     Unknown,
+    #[default]
     Trading,
     Halted,
     Auction,
 }
 
-impl Default for PriceStatus {
-    fn default() -> Self {
-        PriceStatus::Trading
-    }
-}
-
-#[derive(Copy, Clone)]
+#[derive(Default, Copy, Clone)]
 #[repr(C)]
 pub enum CorpAction {
+    #[default]
     NoCorpAct,
 }
 
-impl Default for CorpAction {
-    fn default() -> Self {
-        CorpAction::NoCorpAct
-    }
-}
-
 #[derive(Default, Copy, Clone)]
 #[repr(C)]
 pub struct PriceInfo {
@@ -54,21 +44,16 @@ pub struct PriceComp {
     latest: PriceInfo,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Default, Copy, Clone)]
 #[repr(C)]
 #[allow(dead_code, clippy::upper_case_acronyms)]
 pub enum PriceType {
     Unknown,
+    #[default]
     Price,
 }
 
-impl Default for PriceType {
-    fn default() -> Self {
-        PriceType::Price
-    }
-}
-
-#[derive(Copy, Clone)]
+#[derive(Default, Copy, Clone)]
 #[repr(C)]
 pub struct Rational {
     pub val: i64,
@@ -76,16 +61,6 @@ pub struct Rational {
     pub denom: i64,
 }
 
-impl Default for Rational {
-    fn default() -> Self {
-        Rational {
-            val: 0,
-            numer: 0,
-            denom: 0,
-        }
-    }
-}
-
 #[derive(Default, Copy, Clone)]
 #[repr(C)]
 pub struct Price {
@@ -3,16 +3,26 @@ import { Program } from "@coral-xyz/anchor";
 import { CommiMerkle } from "../target/types/commi_merkle";
 import { MockPythPull } from "../target/types/mock_pyth_pull";
 import { MockPythPush } from "../target/types/mock_pyth_push";
-import { PublicKey, Keypair, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import {
+  PublicKey,
+  Keypair,
+  LAMPORTS_PER_SOL,
+  ComputeBudgetProgram,
+  Ed25519Program,
+  SystemProgram,
+  SYSVAR_INSTRUCTIONS_PUBKEY
+} from "@solana/web3.js";
 import { 
   TOKEN_PROGRAM_ID, 
   createMint,
   mintTo,
   getAssociatedTokenAddress,
   getAccount,
-  createAssociatedTokenAccount
+  createAssociatedTokenAccount,
+  getOrCreateAssociatedTokenAccount
 } from "@solana/spl-token";
 import { sha256 } from "js-sha256";
+import { keccak_256 } from "@noble/hashes/sha3";
 import { assert } from "chai";
 import * as fs from "fs";
 
@@ -33,10 +43,12 @@ describe("commi-merkle", () => {
 
   const program = anchor.workspace.CommiMerkle as Program<CommiMerkle>;
   const pythPullProgram = anchor.workspace.MockPythPull as Program<MockPythPull>;
+  const pythPushProgram = anchor.workspace.MockPythPush as Program<MockPythPush>;
   
   // Test accounts
   let launcher: Keypair;
   let distributor: Keypair;
+  let feeReceiver: Keypair;
   let claimer1: Keypair;
   let claimer2: Keypair;
   let mint: PublicKey;
@@ -44,13 +56,29 @@ describe("commi-merkle", () => {
   let campaignPda: PublicKey;
   let vaultPda: PublicKey;
   let pythPriceAccount: Keypair;
+  let configPda: PublicKey;
+  let feeConfigPda: PublicKey;
+  let programDataPda: PublicKey;
 
   
   // Test constants
   const fundAmount = new anchor.BN(10000000000); // 10 billion tokens with 9 decimals
   const claimAmount1 = new anchor.BN(100000000); // 100 million tokens
   const claimAmount2 = new anchor.BN(200000000); // 200 million tokens
+  const claimAll = new anchor.BN("18446744073709551615"); // u64::MAX, capped at the stored reward
+  const HASH_ALGO_SHA256 = 0;
+  const HASH_ALGO_KECCAK256 = 1;
   const launchTime = new Date().getTime();
+  const campaignEndTs = new anchor.BN(Math.floor(launchTime / 1000) + 3600); // 1 hour campaign
+  const campaignSchedule = {
+    startTs: new anchor.BN(0),
+    endTs: campaignEndTs,
+    claimOpenTs: new anchor.BN(0),
+    claimCloseTs: new anchor.BN(0), // Claims stay open until clawback
+    vestingCliff: new anchor.BN(0),
+    vestingDuration: new anchor.BN(0), // Pay claims out immediately
+    rootGraceSlots: new anchor.BN(150), // Replaced roots verify for ~1 minute
+  };
   
   // Merkle tree setup for launch (32 leaves)
   let launchMerkleTree: Buffer[][];
@@ -73,12 +101,14 @@ describe("commi-merkle", () => {
       throw e;
     }
     
+    feeReceiver = Keypair.generate();
     claimer1 = Keypair.generate();
     claimer2 = Keypair.generate();
     
     // Airdrop SOL to test accounts
     await provider.connection.requestAirdrop(launcher.publicKey, 10 * LAMPORTS_PER_SOL);
     await provider.connection.requestAirdrop(distributor.publicKey, 10 * LAMPORTS_PER_SOL);
+    await provider.connection.requestAirdrop(feeReceiver.publicKey, LAMPORTS_PER_SOL);
     await provider.connection.requestAirdrop(claimer1.publicKey, 10 * LAMPORTS_PER_SOL);
     await provider.connection.requestAirdrop(claimer2.publicKey, 10 * LAMPORTS_PER_SOL);
     
@@ -108,10 +138,24 @@ describe("commi-merkle", () => {
       mint,
       launcherAta,
       launcher,
-      fundAmount.toNumber() * 2 // Mint extra for testing
+      fundAmount.toNumber() * 10 // Mint extra for testing
     );
     
     // Derive PDAs
+    [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      program.programId
+    );
+
+    [feeConfigPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_config")],
+      program.programId
+    );
+    [programDataPda] = PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    );
+
     const [campaign, _campaignBump] = PublicKey.findProgramAddressSync(
       [
         Buffer.from("campaign"),
@@ -157,7 +201,7 @@ describe("commi-merkle", () => {
 
   function setupLaunchMerkleTree() {
     // Generate 32 leaves for launch merkle tree
-    // First leaf: launcher with fund amount
+    // Index 0 is the unallocated pool and never holds a claimable leaf
     const nonces: anchor.BN[] = [];
     
     // Generate random nonces for all 32 leaves
@@ -165,17 +209,9 @@ describe("commi-merkle", () => {
       nonces.push(new anchor.BN(Math.floor(Math.random() * 1000000)));
     }
     
-    // First leaf is the launcher
-    merkleLeaves.push({
-      claimer:launcher.publicKey, 
-      amount: fundAmount, 
-      index: new anchor.BN(0),
-      nonce: nonces[0],
-    });
-    
-    // Remaining 31 leaves are zero address with 0 amount
+    // All 32 leaves start as zero address with 0 amount
     const zeroAddress = PublicKey.default;
-    for (let i = 1; i < 32; i++) {
+    for (let i = 0; i < 32; i++) {
       merkleLeaves.push({
         claimer: zeroAddress, 
         amount: new anchor.BN(0), 
@@ -236,14 +272,17 @@ describe("commi-merkle", () => {
     // Create leaves for two claimers
     updateMerkleTree(1, claimer1.publicKey, claimAmount1);
     updateMerkleTree(2, claimer2.publicKey, claimAmount2);
-    updateMerkleTree(0, launcher.publicKey, fundAmount.sub(claimAmount1).sub(claimAmount2));
     claimMerkleTree = generateMerkleTree(merkleLeaves);
   }
   
-  function createLeafHash(claimer: PublicKey, amount: anchor.BN, index: anchor.BN, nonce: anchor.BN): Buffer {
+  // Leaves and inner nodes are domain-separated with 0x00 / 0x01 prefixes
+  type HashFn = (data: Buffer) => Iterable<number>;
+
+  function createLeafHash(claimer: PublicKey, amount: anchor.BN, index: anchor.BN, nonce: anchor.BN, hash: HashFn = sha256.array): Buffer {
     return Buffer.from(
-      sha256.array(
+      hash(
         Buffer.concat([
+          Buffer.from([0x00]),
           claimer.toBuffer(),
           amount.toArrayLike(Buffer, "le", 8),
           index.toArrayLike(Buffer, "le", 8),
@@ -253,22 +292,22 @@ describe("commi-merkle", () => {
     );
   }
   
-  function hashPair(left: Buffer, right: Buffer): Buffer {
+  function hashPair(left: Buffer, right: Buffer, hash: HashFn = sha256.array): Buffer {
     return Buffer.from(
-      sha256.array(Buffer.concat([left, right]))
+      hash(Buffer.concat([Buffer.from([0x01]), left, right]))
     );
   }
   
-  function generateMerkleTree(leaves: MerkleLeaf[]): Buffer[][] {
+  function generateMerkleTree(leaves: MerkleLeaf[], hash: HashFn = sha256.array): Buffer[][] {
     let result: Buffer[][] = [];
-    let currentLevel = leaves.map(leaf => createLeafHash(leaf.claimer, leaf.amount, leaf.index, leaf.nonce));
+    let currentLevel = leaves.map(leaf => createLeafHash(leaf.claimer, leaf.amount, leaf.index, leaf.nonce, hash));
     result.push(currentLevel);
 
     // Build tree level by level
     while (currentLevel.length > 1) {
       const nextLevel: Buffer[] = [];
       for (let i = 0; i < currentLevel.length; i += 2) {
-        nextLevel.push(hashPair(currentLevel[i], currentLevel[i + 1]));
+        nextLevel.push(hashPair(currentLevel[i], currentLevel[i + 1], hash));
       }
       result.push(nextLevel);
       currentLevel = nextLevel;
@@ -276,503 +315,2778 @@ describe("commi-merkle", () => {
     return result;
   }
 
-  describe("launch", () => {
+  describe("initialize_config", () => {
+    it("should fail when signer is not the upgrade authority", async () => {
+      const impostor = Keypair.generate();
+      await provider.connection.requestAirdrop(impostor.publicKey, LAMPORTS_PER_SOL);
+      await new Promise(resolve => setTimeout(resolve, 1000));
 
-    it("should fail when fund amount is below minimum (10000)", async () => {
-      const belowMinimum = new anchor.BN(9999);
-      
-      // Create a merkle root for below minimum fund
-      const belowMinLeaves: MerkleLeaf[] = [];
-      const belowMinNonces: anchor.BN[] = [];
-      for (let i = 0; i < 32; i++) {
-        belowMinNonces.push(new anchor.BN(Math.floor(Math.random() * 1000000)));
-      }
-      belowMinLeaves.push({
-        claimer: launcher.publicKey, 
-        amount: belowMinimum, 
-        index: new anchor.BN(0),
-        nonce: belowMinNonces[0]
-      });
-      const zeroAddress = PublicKey.default;
-      for (let i = 1; i < 32; i++) {
-        belowMinLeaves.push({
-          claimer: zeroAddress, 
-          amount: new anchor.BN(0), 
-          index: new anchor.BN(i),
-          nonce: belowMinNonces[i]
-        });
-      }
-      const belowMinMerkleTree = generateMerkleTree(belowMinLeaves);
-      
       try {
         await program.methods
-          .launch(new anchor.BN(launchTime), belowMinimum)
+          .initializeConfig(distributor.publicKey, pythPriceAccount.publicKey)
           .accounts({
-            launcher: launcher.publicKey,
-            distributor: distributor.publicKey,
-            campaign: campaignPda,
-            mint,
-            launcherAta,
-            vault: vaultPda,
-            priceUpdate: pythPriceAccount.publicKey,
-            tokenProgram: TOKEN_PROGRAM_ID,
+            admin: impostor.publicKey,
+            config: configPda,
+            programData: programDataPda,
           })
-          .signers([launcher])
+          .signers([impostor])
           .rpc();
-        
-        assert.fail("Should have failed with InvalidFund error");
+
+        assert.fail("Should have failed with InvalidAdmin error");
       } catch (error) {
-        assert.include(error.toString(), "InvalidFund");
+        assert.include(error.toString(), "InvalidAdmin");
       }
     });
 
-    it("should launch a new campaign successfully", async () => {
-      const merkleRoot = launchMerkleTree[launchMerkleTree.length - 1][0];
-      
-      // Get balances before transaction
-      const distributorBalanceBefore = await provider.connection.getBalance(distributor.publicKey);
-      
+    it("should initialize config successfully", async () => {
       const tx = await program.methods
-        .launch(new anchor.BN(launchTime), fundAmount)
+        .initializeConfig(distributor.publicKey, pythPriceAccount.publicKey)
         .accounts({
-          launcher: launcher.publicKey,
-          distributor: distributor.publicKey,
-          campaign: campaignPda,
-          mint,
-          launcherAta,
-          vault: vaultPda,
-          priceUpdate: pythPriceAccount.publicKey,
-          tokenProgram: TOKEN_PROGRAM_ID,
+          admin: admin.publicKey,
+          config: configPda,
+          programData: programDataPda,
         })
-        .signers([launcher])
         .rpc();
-      
-      console.log("Launch transaction signature:", tx);
 
-      await new Promise(resolve => setTimeout(resolve, 1000));
-      // Get transaction details for gas consumption
-      const txDetails = await provider.connection.getTransaction(tx, {
-        commitment: "confirmed",
-        maxSupportedTransactionVersion: 0
-      });
+      console.log("Initialize config transaction signature:", tx);
 
-      const gasUsed = txDetails?.meta?.fee || 0;
-      console.log("Gas consumed:", gasUsed, "lamports");
-      
-      // Get balances after transaction
-      const distributorBalanceAfter = await provider.connection.getBalance(distributor.publicKey);
-      
-      // Calculate service fee transferred (should be ~$5 worth of SOL)
-      const serviceFeeTransferred = distributorBalanceAfter - distributorBalanceBefore;
-      console.log("Service fee transferred:", serviceFeeTransferred, "lamports");
-      
-      // Verify service fee was transferred (checking it's greater than 0 and reasonable)
-      // With SOL at ~$200, $5 would be ~0.025 SOL = 25,000,000 lamports
-      // But since we're using mock Pyth with price 200.00000000, we expect:
-      // $5 / $200 = 0.025 SOL = 25,000,000 lamports
-      assert.isAbove(serviceFeeTransferred, 0, "Service fee should be transferred");
-      assert.isBelow(serviceFeeTransferred, 100_000_000, "Service fee should be reasonable (less than 0.1 SOL)");
-      
-      // Verify campaign state
-      const campaignAccount = await program.account.campaignState.fetch(campaignPda);
-      assert.equal(campaignAccount.launcher.toString(), launcher.publicKey.toString());
-      assert.equal(campaignAccount.mint.toString(), mint.toString());
-      assert.equal(campaignAccount.fund.toString(), fundAmount.toString());
-      // assert.equal(
-      //   Buffer.from(campaignAccount.merkleRoot).toString("hex"),
-      //   merkleRoot.toString("hex")
-      // );
-      assert.equal(campaignAccount.rewards.length, 32);
-      assert.equal(campaignAccount.rewards[0].toString(), fundAmount.toString());
-      
-      // Verify vault received tokens
-      const vaultAccount = await getAccount(provider.connection, vaultPda);
-      assert.equal(vaultAccount.amount.toString(), fundAmount.toString());
+      const configAccount = await program.account.config.fetch(configPda);
+      assert.equal(configAccount.admin.toString(), admin.publicKey.toString());
+      assert.deepEqual(configAccount.distributors.map(d => d.toString()), [distributor.publicKey.toString()]);
+      assert.equal(configAccount.priceFeed.toString(), pythPriceAccount.publicKey.toString());
     });
   });
 
-  describe("lock/unlock", () => {
-    it("should lock campaign successfully", async () => {
-      const tx = await program.methods
-        .lock()
+  describe("fee config", () => {
+    it("should only allow admin to update the fee config", async () => {
+      try {
+        await program.methods
+          .updateFeeConfig(feeReceiver.publicKey, new anchor.BN(500), [], [])
+          .accounts({
+            admin: distributor.publicKey,
+            config: configPda,
+            feeConfig: feeConfigPda,
+          })
+          .signers([distributor])
+          .rpc();
+
+        assert.fail("Should have failed with InvalidAdmin error");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidAdmin");
+      }
+    });
+
+    it("should reject more waivers than the fee config holds", async () => {
+      const waivers = Array.from({ length: 17 }, () => Keypair.generate().publicKey);
+      try {
+        await program.methods
+          .updateFeeConfig(feeReceiver.publicKey, new anchor.BN(500), waivers, [])
+          .accounts({
+            admin: admin.publicKey,
+            config: configPda,
+            feeConfig: feeConfigPda,
+          })
+          .rpc();
+
+        assert.fail("Should have failed with TooManyFeeWaivers error");
+      } catch (error) {
+        assert.include(error.toString(), "TooManyFeeWaivers");
+      }
+    });
+
+    it("should set the fee schedule", async () => {
+      await program.methods
+        .updateFeeConfig(feeReceiver.publicKey, new anchor.BN(500), [], [])
         .accounts({
-          distributor: distributor.publicKey,
-          campaign: campaignPda,
-          mint
+          admin: admin.publicKey,
+          config: configPda,
+          feeConfig: feeConfigPda,
         })
-        .signers([distributor])
         .rpc();
-      
-      console.log("Lock transaction signature:", tx);
-      
-      // Verify campaign is locked
-      const campaignAccount = await program.account.campaignState.fetch(campaignPda);
-      assert.equal(campaignAccount.locked, 1, "Campaign should be locked");
+
+      const feeConfigAccount = await program.account.feeConfig.fetch(feeConfigPda);
+      assert.equal(feeConfigAccount.feeReceiver.toString(), feeReceiver.publicKey.toString());
+      assert.equal(feeConfigAccount.feeUsdCents.toNumber(), 500);
+      assert.deepEqual(feeConfigAccount.waivers, []);
+      assert.deepEqual(feeConfigAccount.feeMints, []);
     });
+  });
 
-    it("should prevent claims when campaign is locked", async () => {
-      // First ensure campaign is locked
-      const campaignAccount = await program.account.campaignState.fetch(campaignPda);
-      assert.equal(campaignAccount.locked, 1, "Campaign should be locked for this test");
+  describe("oracle policy", () => {
+    const MIN_VERIFICATION_FULL = 255;
 
-      // Create a new claimer for this test
-      const testClaimer = Keypair.generate();
-      await provider.connection.requestAirdrop(testClaimer.publicKey, LAMPORTS_PER_SOL);
-      await new Promise(resolve => setTimeout(resolve, 1000));
+    async function quoteFee(fallbackPriceUpdate: PublicKey | null = null) {
+      return program.methods
+        .quoteFee(launcher.publicKey)
+        .accounts({
+          config: configPda,
+          feeConfig: feeConfigPda,
+          priceUpdate: pythPriceAccount.publicKey,
+          fallbackPriceUpdate,
+        })
+        .view();
+    }
 
-      const testClaimerAta = await getAssociatedTokenAddress(
-        mint,
-        testClaimer.publicKey
-      );
+    // Simulation errors carry the program logs separately from the message
+    function errorText(error: any): string {
+      return [error.toString(), ...(error.logs ?? error.simulationResponse?.logs ?? [])].join("\n");
+    }
+
+    async function setPrice(price: anchor.BN, conf: anchor.BN) {
+      await pythPullProgram.methods
+        .setPrice(price, conf)
+        .accounts({ price: pythPriceAccount.publicKey })
+        .rpc();
+    }
+
+    it("should default to a 1% confidence ratio and full verification", async () => {
+      const configAccount = await program.account.config.fetch(configPda);
+      assert.equal(configAccount.maxConfBps, 100);
+      assert.equal(configAccount.minVerification, MIN_VERIFICATION_FULL);
+    });
 
+    it("should only allow admin to set the oracle policy", async () => {
       try {
         await program.methods
-          .claim(
-            new anchor.BN(3), // unused index
-            [],
-            new anchor.BN(0)
-          )
+          .setOraclePolicy(50, MIN_VERIFICATION_FULL)
           .accounts({
-            claimer: testClaimer.publicKey,
-            launcher: launcher.publicKey,
-            campaign: campaignPda,
-            mint,
-            vault: vaultPda,
-            claimerAta: testClaimerAta,
-            tokenProgram: TOKEN_PROGRAM_ID,
+            admin: distributor.publicKey,
+            config: configPda,
           })
-          .signers([testClaimer])
+          .signers([distributor])
           .rpc();
-        
-        assert.fail("Should have failed with CampaignLocked error");
+
+        assert.fail("Should have failed with InvalidAdmin error");
       } catch (error) {
-        assert.include(error.toString(), "CampaignLocked");
+        assert.include(error.toString(), "InvalidAdmin");
       }
     });
 
-    it("should only allow distributor to lock campaign", async () => {
-      const unauthorizedUser = Keypair.generate();
-      await provider.connection.requestAirdrop(unauthorizedUser.publicKey, LAMPORTS_PER_SOL);
-      await new Promise(resolve => setTimeout(resolve, 1000));
-
+    it("should reject a zero confidence ratio", async () => {
       try {
         await program.methods
-          .lock()
+          .setOraclePolicy(0, MIN_VERIFICATION_FULL)
           .accounts({
-            distributor: unauthorizedUser.publicKey,
-            campaign: campaignPda,
-            mint
+            admin: admin.publicKey,
+            config: configPda,
           })
-          .signers([unauthorizedUser])
           .rpc();
-        
-        assert.fail("Should have failed with InvalidDistributor error");
+
+        assert.fail("Should have failed with InvalidOraclePolicy error");
       } catch (error) {
-        assert.include(error.toString(), "InvalidDistributor");
+        assert.include(error.toString(), "InvalidOraclePolicy");
       }
     });
-  });
 
-  describe("update", () => {
-    it("should update merkle root and rewards successfully", async () => {
-      const claimMerkleRoot = claimMerkleTree[claimMerkleTree.length - 1][0];
-      // Update rewards for claimer1 (index 1) and claimer2 (index 2)
-      const participants = [
-        [new anchor.BN(1), claimAmount1],
-        [new anchor.BN(2), claimAmount2]
-      ];
-      
-      const tx = await program.methods
-        .update(Array.from(claimMerkleRoot), participants)
+    it("should reject prices with a wide confidence interval", async () => {
+      // Mock confidence is 0.1 on a 200 price, 5 bps
+      await program.methods
+        .setOraclePolicy(4, MIN_VERIFICATION_FULL)
         .accounts({
-          distributor: distributor.publicKey,
-          launcher: launcher.publicKey,
-          campaign: campaignPda,
-          mint
+          admin: admin.publicKey,
+          config: configPda,
         })
-        .signers([distributor])
         .rpc();
-      
-      console.log("Update transaction signature:", tx);
-      await new Promise(resolve => setTimeout(resolve, 1000));
-      // Get transaction details for gas consumption
-      const txDetails = await provider.connection.getTransaction(tx, {
-        commitment: "confirmed",
-        maxSupportedTransactionVersion: 0
-      });
-      const gasUsed = txDetails?.meta?.fee || 0;
-      console.log("Gas consumed:", gasUsed, "lamports");
-      
-      // Verify merkle root was updated and rewards were set
-      const campaignAccount = await program.account.campaignState.fetch(campaignPda);
-      assert.equal(
-        Buffer.from(campaignAccount.merkleRoot).toString("hex"),
-        claimMerkleRoot.toString("hex")
-      );
-      assert.equal(campaignAccount.rewards[1].toString(), claimAmount1.toString());
-      assert.equal(campaignAccount.rewards[2].toString(), claimAmount2.toString());
-    });
-    
-    it("should resize rewards array and update in single transaction", async () => {
-      expandMerkleTree();
-      expandedMerkleTree = generateMerkleTree(merkleLeaves);
-      let expandedMerkleRoot = expandedMerkleTree[expandedMerkleTree.length - 1][0];
-      const participants: any[] = []; // No new participants in this test
-      
-      // Create both instructions
-      const extendIx = await program.methods
-        .extend(new anchor.BN(132)) // Extend to 132 participants (32 + 100)
-        .accounts({
-          distributor: distributor.publicKey,
-          campaign: campaignPda,
-        })
-        .instruction();
-      
-      const updateIx = await program.methods
-        .update(Array.from(expandedMerkleRoot), participants)
+
+      try {
+        await quoteFee();
+        assert.fail("Should have failed with PriceConfidenceTooWide error");
+      } catch (error) {
+        assert.include(errorText(error), "PriceConfidenceTooWide");
+      }
+
+      await program.methods
+        .setOraclePolicy(100, MIN_VERIFICATION_FULL)
         .accounts({
-          distributor: distributor.publicKey,
-          launcher: launcher.publicKey,
-          campaign: campaignPda,
-          mint
+          admin: admin.publicKey,
+          config: configPda,
         })
-        .instruction();
-      
-      // Send both instructions in a single transaction
-      const tx = new anchor.web3.Transaction().add(extendIx, updateIx);
-      const signature = await provider.sendAndConfirm(tx, [distributor]);      
-      
-      console.log("Update with resize transaction signature:", signature);
+        .rpc();
+      assert.equal((await quoteFee()).toNumber(), 25_000_000);
+    });
+
+    it("should reject non-positive prices", async () => {
+      await setPrice(new anchor.BN(-20000000000), new anchor.BN(10000000));
+      try {
+        await quoteFee();
+        assert.fail("Should have failed with NonPositivePrice error");
+      } catch (error) {
+        assert.include(errorText(error), "NonPositivePrice");
+      } finally {
+        await setPrice(new anchor.BN(20000000000), new anchor.BN(10000000));
+      }
+    });
+
+    it("should let only the admin move the primary price feed", async () => {
+      const newPriceAccount = await setupMockPythPriceAccount();
+
+      try {
+        await program.methods
+          .setPriceFeed(newPriceAccount.publicKey)
+          .accounts({
+            admin: distributor.publicKey,
+            config: configPda,
+          })
+          .signers([distributor])
+          .rpc();
+
+        assert.fail("Should have failed with InvalidAdmin error");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidAdmin");
+      }
+
+      try {
+        await program.methods
+          .setPriceFeed(PublicKey.default)
+          .accounts({
+            admin: admin.publicKey,
+            config: configPda,
+          })
+          .rpc();
+
+        assert.fail("Should have failed with InvalidPriceFeed error");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidPriceFeed");
+      }
+
+      await program.methods
+        .setPriceFeed(newPriceAccount.publicKey)
+        .accounts({
+          admin: admin.publicKey,
+          config: configPda,
+        })
+        .rpc();
+      let configAccount = await program.account.config.fetch(configPda);
+      assert.equal(configAccount.priceFeed.toString(), newPriceAccount.publicKey.toString());
+
+      // The old account is no longer accepted for pricing
+      try {
+        await quoteFee();
+        assert.fail("Should have failed with InvalidPriceFeed error");
+      } catch (error) {
+        assert.include(errorText(error), "InvalidPriceFeed");
+      }
+
+      await program.methods
+        .setPriceFeed(pythPriceAccount.publicKey)
+        .accounts({
+          admin: admin.publicKey,
+          config: configPda,
+        })
+        .rpc();
+      configAccount = await program.account.config.fetch(configPda);
+      assert.equal(configAccount.priceFeed.toString(), pythPriceAccount.publicKey.toString());
+      assert.equal((await quoteFee()).toNumber(), 25_000_000);
+    });
+
+    describe("fallback feed", () => {
+      const FALLBACK_PUSH = 1;
+      const pushPriceAccount = Keypair.generate();
+
+      before(async () => {
+        // Legacy pc::Price accounts are 3312 bytes owned by the push oracle
+        const space = 3312;
+        await provider.sendAndConfirm(
+          new anchor.web3.Transaction().add(
+            SystemProgram.createAccount({
+              fromPubkey: admin.publicKey,
+              newAccountPubkey: pushPriceAccount.publicKey,
+              lamports: await provider.connection.getMinimumBalanceForRentExemption(space),
+              space,
+              programId: pythPushProgram.programId,
+            })
+          ),
+          [pushPriceAccount]
+        );
+        await pythPushProgram.methods
+          .initialize(new anchor.BN(20000000000), -8, new anchor.BN(10000000))
+          .accounts({ price: pushPriceAccount.publicKey })
+          .rpc();
+      });
+
+      it("should only allow admin to set the fallback feed", async () => {
+        try {
+          await program.methods
+            .setFallbackFeed(pushPriceAccount.publicKey, FALLBACK_PUSH)
+            .accounts({
+              admin: distributor.publicKey,
+              config: configPda,
+            })
+            .signers([distributor])
+            .rpc();
+
+          assert.fail("Should have failed with InvalidAdmin error");
+        } catch (error) {
+          assert.include(error.toString(), "InvalidAdmin");
+        }
+      });
+
+      it("should reject the primary feed or an unknown kind as fallback", async () => {
+        try {
+          await program.methods
+            .setFallbackFeed(pythPriceAccount.publicKey, FALLBACK_PUSH)
+            .accounts({
+              admin: admin.publicKey,
+              config: configPda,
+            })
+            .rpc();
+
+          assert.fail("Should have failed with InvalidPriceFeed error");
+        } catch (error) {
+          assert.include(error.toString(), "InvalidPriceFeed");
+        }
+
+        try {
+          await program.methods
+            .setFallbackFeed(pushPriceAccount.publicKey, 2)
+            .accounts({
+              admin: admin.publicKey,
+              config: configPda,
+            })
+            .rpc();
+
+          assert.fail("Should have failed with InvalidOraclePolicy error");
+        } catch (error) {
+          assert.include(error.toString(), "InvalidOraclePolicy");
+        }
+      });
+
+      it("should set a push fallback and keep pricing from a fresh primary", async () => {
+        await program.methods
+          .setFallbackFeed(pushPriceAccount.publicKey, FALLBACK_PUSH)
+          .accounts({
+            admin: admin.publicKey,
+            config: configPda,
+          })
+          .rpc();
+
+        const configAccount = await program.account.config.fetch(configPda);
+        assert.equal(configAccount.fallbackFeed.toString(), pushPriceAccount.publicKey.toString());
+        assert.equal(configAccount.fallbackKind, FALLBACK_PUSH);
+
+        assert.equal((await quoteFee(pushPriceAccount.publicKey)).toNumber(), 25_000_000);
+        assert.equal((await quoteFee()).toNumber(), 25_000_000);
+      });
+
+      it("should reject a fallback account other than the configured one", async () => {
+        try {
+          await quoteFee(Keypair.generate().publicKey);
+          assert.fail("Should have failed with InvalidPriceFeed error");
+        } catch (error) {
+          assert.include(errorText(error), "InvalidPriceFeed");
+        }
+      });
+    });
+  });
+
+  describe("distributor allowlist", () => {
+    let newDistributor: Keypair;
+
+    before(async () => {
+      newDistributor = Keypair.generate();
+      await provider.connection.requestAirdrop(newDistributor.publicKey, LAMPORTS_PER_SOL);
+      await new Promise(resolve => setTimeout(resolve, 1000));
+    });
+
+    it("should only allow admin to propose a distributor", async () => {
+      try {
+        await program.methods
+          .proposeDistributor(newDistributor.publicKey)
+          .accounts({
+            admin: distributor.publicKey,
+            config: configPda,
+          })
+          .signers([distributor])
+          .rpc();
+
+        assert.fail("Should have failed with InvalidAdmin error");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidAdmin");
+      }
+    });
+
+    it("should only allow the pending distributor to accept", async () => {
+      await program.methods
+        .proposeDistributor(newDistributor.publicKey)
+        .accounts({
+          admin: admin.publicKey,
+          config: configPda,
+        })
+        .rpc();
+
+      try {
+        await program.methods
+          .acceptDistributor()
+          .accounts({
+            pendingDistributor: distributor.publicKey,
+            config: configPda,
+          })
+          .signers([distributor])
+          .rpc();
+
+        assert.fail("Should have failed with InvalidPendingDistributor error");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidPendingDistributor");
+      }
+    });
+
+    it("should allowlist distributor after acceptance", async () => {
+      await program.methods
+        .acceptDistributor()
+        .accounts({
+          pendingDistributor: newDistributor.publicKey,
+          config: configPda,
+        })
+        .signers([newDistributor])
+        .rpc();
+
+      const configAccount = await program.account.config.fetch(configPda);
+      assert.deepEqual(
+        configAccount.distributors.map(d => d.toString()),
+        [distributor.publicKey.toString(), newDistributor.publicKey.toString()]
+      );
+      assert.equal(configAccount.pendingDistributor.toString(), PublicKey.default.toString());
+    });
+
+    it("should remove distributor from allowlist", async () => {
+      await program.methods
+        .removeDistributor(newDistributor.publicKey)
+        .accounts({
+          admin: admin.publicKey,
+          config: configPda,
+        })
+        .rpc();
+
+      const configAccount = await program.account.config.fetch(configPda);
+      assert.deepEqual(configAccount.distributors.map(d => d.toString()), [distributor.publicKey.toString()]);
+    });
+  });
+
+  describe("admin rotation", () => {
+    let newAdmin: Keypair;
+
+    before(async () => {
+      newAdmin = Keypair.generate();
+      await provider.connection.requestAirdrop(newAdmin.publicKey, LAMPORTS_PER_SOL);
+      await new Promise(resolve => setTimeout(resolve, 1000));
+    });
+
+    it("should only allow the pending admin to accept", async () => {
+      await program.methods
+        .proposeAdmin(newAdmin.publicKey)
+        .accounts({
+          admin: admin.publicKey,
+          config: configPda,
+        })
+        .rpc();
+
+      try {
+        await program.methods
+          .acceptAdmin()
+          .accounts({
+            pendingAdmin: distributor.publicKey,
+            config: configPda,
+          })
+          .signers([distributor])
+          .rpc();
+
+        assert.fail("Should have failed with InvalidPendingAdmin error");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidPendingAdmin");
+      }
+    });
+
+    it("should rotate the admin and emit an AcceptAdminEvent", async () => {
+      const tx = await program.methods
+        .acceptAdmin()
+        .accounts({
+          pendingAdmin: newAdmin.publicKey,
+          config: configPda,
+        })
+        .signers([newAdmin])
+        .rpc({ commitment: "confirmed" });
+
+      const txDetails = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0
+      });
+      const eventParser = new anchor.EventParser(program.programId, program.coder);
+      const events = Array.from(eventParser.parseLogs(txDetails.meta.logMessages));
+      const rotated = events.find(event => event.name === "acceptAdminEvent");
+      assert.exists(rotated, "Accept did not emit an AcceptAdminEvent");
+      assert.equal(rotated.data.previousAdmin.toString(), admin.publicKey.toString());
+      assert.equal(rotated.data.admin.toString(), newAdmin.publicKey.toString());
+
+      const configAccount = await program.account.config.fetch(configPda);
+      assert.equal(configAccount.admin.toString(), newAdmin.publicKey.toString());
+      assert.equal(configAccount.pendingAdmin.toString(), PublicKey.default.toString());
+    });
+
+    it("should revoke the previous admin's access to pause", async () => {
+      try {
+        await program.methods
+          .pause(1) // PAUSE_LAUNCH
+          .accounts({
+            admin: admin.publicKey,
+            config: configPda,
+          })
+          .rpc();
+
+        assert.fail("Should have failed with InvalidAdmin error");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidAdmin");
+      }
+    });
+
+    after(async () => {
+      // Hand the config back so the remaining suites keep using the provider wallet
+      await program.methods
+        .proposeAdmin(admin.publicKey)
+        .accounts({
+          admin: newAdmin.publicKey,
+          config: configPda,
+        })
+        .signers([newAdmin])
+        .rpc();
+      await program.methods
+        .acceptAdmin()
+        .accounts({
+          pendingAdmin: admin.publicKey,
+          config: configPda,
+        })
+        .rpc();
+    });
+  });
+
+  describe("launch", () => {
+
+    it("should fail when fund amount is below minimum (10000)", async () => {
+      const belowMinimum = new anchor.BN(9999);
+      
+      // Create a merkle root for below minimum fund
+      const belowMinLeaves: MerkleLeaf[] = [];
+      const belowMinNonces: anchor.BN[] = [];
+      for (let i = 0; i < 32; i++) {
+        belowMinNonces.push(new anchor.BN(Math.floor(Math.random() * 1000000)));
+      }
+      belowMinLeaves.push({
+        claimer: launcher.publicKey, 
+        amount: belowMinimum, 
+        index: new anchor.BN(0),
+        nonce: belowMinNonces[0]
+      });
+      const zeroAddress = PublicKey.default;
+      for (let i = 1; i < 32; i++) {
+        belowMinLeaves.push({
+          claimer: zeroAddress, 
+          amount: new anchor.BN(0), 
+          index: new anchor.BN(i),
+          nonce: belowMinNonces[i]
+        });
+      }
+      const belowMinMerkleTree = generateMerkleTree(belowMinLeaves);
+      
+      try {
+        await program.methods
+          .launch(new anchor.BN(launchTime), belowMinimum, campaignSchedule, HASH_ALGO_SHA256)
+          .accounts({
+            launcher: launcher.publicKey,
+            distributor: distributor.publicKey,
+            feeReceiver: feeReceiver.publicKey,
+            config: configPda,
+            feeConfig: feeConfigPda,
+            campaign: campaignPda,
+            mint,
+            launcherAta,
+            vault: vaultPda,
+            priceUpdate: pythPriceAccount.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([launcher])
+          .rpc();
+        
+        assert.fail("Should have failed with InvalidFund error");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidFund");
+      }
+    });
+
+    it("should reject a root grace window above the cap", async () => {
+      try {
+        await program.methods
+          .launch(new anchor.BN(launchTime), fundAmount, {
+            ...campaignSchedule,
+            rootGraceSlots: new anchor.BN(9_001),
+          }, HASH_ALGO_SHA256)
+          .accounts({
+            launcher: launcher.publicKey,
+            distributor: distributor.publicKey,
+            feeReceiver: feeReceiver.publicKey,
+            config: configPda,
+            feeConfig: feeConfigPda,
+            campaign: campaignPda,
+            mint,
+            launcherAta,
+            vault: vaultPda,
+            priceUpdate: pythPriceAccount.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([launcher])
+          .rpc();
+
+        assert.fail("Should have failed with InvalidSchedule error");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidSchedule");
+      }
+    });
+
+    it("should launch a new campaign successfully", async () => {
+      const merkleRoot = launchMerkleTree[launchMerkleTree.length - 1][0];
+      
+      // Quote the fee through simulation before signing
+      const quotedFee = await program.methods
+        .quoteFee(launcher.publicKey)
+        .accounts({
+          config: configPda,
+          feeConfig: feeConfigPda,
+          priceUpdate: pythPriceAccount.publicKey,
+        })
+        .view();
+
+      // Get balances before transaction
+      const feeReceiverBalanceBefore = await provider.connection.getBalance(feeReceiver.publicKey);
+      
+      const tx = await program.methods
+        .launch(new anchor.BN(launchTime), fundAmount, campaignSchedule, HASH_ALGO_SHA256)
+        .accounts({
+          launcher: launcher.publicKey,
+          distributor: distributor.publicKey,
+          feeReceiver: feeReceiver.publicKey,
+          config: configPda,
+          feeConfig: feeConfigPda,
+          campaign: campaignPda,
+          mint,
+          launcherAta,
+          vault: vaultPda,
+          priceUpdate: pythPriceAccount.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([launcher])
+        .rpc();
+      
+      console.log("Launch transaction signature:", tx);
+
       await new Promise(resolve => setTimeout(resolve, 1000));
       // Get transaction details for gas consumption
-      const txDetails = await provider.connection.getTransaction(signature, {
+      const txDetails = await provider.connection.getTransaction(tx, {
         commitment: "confirmed",
         maxSupportedTransactionVersion: 0
       });
+
       const gasUsed = txDetails?.meta?.fee || 0;
       console.log("Gas consumed:", gasUsed, "lamports");
       
-      // Verify rewards array was resized
-      const campaignAccount = await program.account.campaignState.fetch(campaignPda);
-      assert.equal(campaignAccount.rewards.length, 132); // Should be increased from 32 to 132 (32 + 100)
-      assert.equal(
-        Buffer.from(campaignAccount.merkleRoot).toString("hex"),
+      // Get balances after transaction
+      const feeReceiverBalanceAfter = await provider.connection.getBalance(feeReceiver.publicKey);
+      
+      // Calculate service fee transferred (should be ~$5 worth of SOL)
+      const serviceFeeTransferred = feeReceiverBalanceAfter - feeReceiverBalanceBefore;
+      console.log("Service fee transferred:", serviceFeeTransferred, "lamports");
+      
+      // Mock Pyth price is 200.00000000, so $5 / $200 = 0.025 SOL = 25,000,000 lamports
+      assert.equal(serviceFeeTransferred, 25_000_000, "Service fee should be exactly $5 of SOL");
+      assert.equal(quotedFee.toNumber(), serviceFeeTransferred, "Quoted fee should match the charged fee");
+      
+      // Verify campaign state
+      const campaignAccount = await program.account.campaignState.fetch(campaignPda);
+      assert.equal(campaignAccount.launcher.toString(), launcher.publicKey.toString());
+      assert.equal(campaignAccount.mint.toString(), mint.toString());
+      assert.equal(campaignAccount.distributor.toString(), distributor.publicKey.toString());
+      assert.equal(campaignAccount.fund.toString(), fundAmount.toString());
+      assert.equal(campaignAccount.hashVersion, 1, "New campaigns should use prefixed hashing");
+      assert.equal(campaignAccount.endTs.toString(), campaignEndTs.toString());
+      // assert.equal(
+      //   Buffer.from(campaignAccount.merkleRoot).toString("hex"),
+      //   merkleRoot.toString("hex")
+      // );
+      assert.equal(campaignAccount.rewards.length, 32);
+      assert.equal(campaignAccount.rewards[0].toString(), fundAmount.toString());
+      
+      // Verify vault received tokens
+      const vaultAccount = await getAccount(provider.connection, vaultPda);
+      assert.equal(vaultAccount.amount.toString(), fundAmount.toString());
+    });
+  });
+
+  describe("fallback pricing", () => {
+    const FALLBACK_PULL = 0;
+    const FALLBACK_PUSH = 1;
+    const fallbackFund = fundAmount; // The launch minimum
+    const pushPriceAccount = Keypair.generate();
+    let pullPriceAccount: Keypair;
+
+    before(async () => {
+      const space = 3312;
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          SystemProgram.createAccount({
+            fromPubkey: admin.publicKey,
+            newAccountPubkey: pushPriceAccount.publicKey,
+            lamports: await provider.connection.getMinimumBalanceForRentExemption(space),
+            space,
+            programId: pythPushProgram.programId,
+          })
+        ),
+        [pushPriceAccount]
+      );
+      await pythPushProgram.methods
+        .initialize(new anchor.BN(20000000000), -8, new anchor.BN(10000000))
+        .accounts({ price: pushPriceAccount.publicKey })
+        .rpc();
+      pullPriceAccount = await setupMockPythPriceAccount();
+
+      // Two minutes old is past the 60 second maximum age
+      await pythPullProgram.methods
+        .setPublishTime(new anchor.BN(Math.floor(Date.now() / 1000) - 120))
+        .accounts({ price: pythPriceAccount.publicKey })
+        .rpc();
+    });
+
+    after(async () => {
+      await pythPullProgram.methods
+        .setPrice(new anchor.BN(20000000000), new anchor.BN(10000000))
+        .accounts({ price: pythPriceAccount.publicKey })
+        .rpc();
+    });
+
+    async function setFallbackFeed(feed: PublicKey, kind: number) {
+      await program.methods
+        .setFallbackFeed(feed, kind)
+        .accounts({
+          admin: admin.publicKey,
+          config: configPda,
+        })
+        .rpc();
+    }
+
+    // Launches a small campaign and returns the LaunchEvent it emitted
+    async function launchWithFallback(seed: anchor.BN, fallbackPriceUpdate: PublicKey | null) {
+      const [campaign] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("campaign"),
+          launcher.publicKey.toBuffer(),
+          mint.toBuffer(),
+          seed.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      const tx = await program.methods
+        .launch(seed, fallbackFund, campaignSchedule, HASH_ALGO_SHA256)
+        .accounts({
+          launcher: launcher.publicKey,
+          distributor: distributor.publicKey,
+          feeReceiver: feeReceiver.publicKey,
+          config: configPda,
+          feeConfig: feeConfigPda,
+          campaign,
+          mint,
+          launcherAta,
+          vault: await getAssociatedTokenAddress(mint, campaign, true),
+          priceUpdate: pythPriceAccount.publicKey,
+          fallbackPriceUpdate,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([launcher])
+        .rpc({ commitment: "confirmed" });
+
+      const txDetails = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0
+      });
+      const eventParser = new anchor.EventParser(program.programId, program.coder);
+      for (const event of eventParser.parseLogs(txDetails.meta.logMessages)) {
+        if (event.name === "launchEvent") {
+          return event.data;
+        }
+      }
+      assert.fail("Launch did not emit a LaunchEvent");
+    }
+
+    it("should reject a stale primary feed without a fallback account", async () => {
+      try {
+        await launchWithFallback(new anchor.BN(launchTime + 7), null);
+        assert.fail("Should have failed with StalePrice error");
+      } catch (error) {
+        assert.include(error.toString(), "StalePrice");
+      }
+    });
+
+    it("should price from the push fallback when the primary is stale", async () => {
+      await setFallbackFeed(pushPriceAccount.publicKey, FALLBACK_PUSH);
+
+      const event = await launchWithFallback(new anchor.BN(launchTime + 8), pushPriceAccount.publicKey);
+      assert.equal(event.priceFeed.toString(), pushPriceAccount.publicKey.toString());
+      assert.equal(event.serviceFee.toNumber(), 25_000_000);
+    });
+
+    it("should price from the pull fallback when the primary is stale", async () => {
+      await setFallbackFeed(pullPriceAccount.publicKey, FALLBACK_PULL);
+
+      const event = await launchWithFallback(new anchor.BN(launchTime + 9), pullPriceAccount.publicKey);
+      assert.equal(event.priceFeed.toString(), pullPriceAccount.publicKey.toString());
+      assert.equal(event.serviceFee.toNumber(), 25_000_000);
+    });
+
+    it("should reject a stale fallback feed", async () => {
+      await pythPullProgram.methods
+        .setPublishTime(new anchor.BN(Math.floor(Date.now() / 1000) - 120))
+        .accounts({ price: pullPriceAccount.publicKey })
+        .rpc();
+
+      try {
+        await launchWithFallback(new anchor.BN(launchTime + 10), pullPriceAccount.publicKey);
+        assert.fail("Should have failed with StalePrice error");
+      } catch (error) {
+        assert.include(error.toString(), "StalePrice");
+      }
+    });
+  });
+
+  describe("top_up", () => {
+    it("should add funds to a live campaign", async () => {
+      const topUpAmount = new anchor.BN(1000000000);
+      const before = await program.account.campaignState.fetch(campaignPda);
+
+      await program.methods
+        .topUp(topUpAmount)
+        .accounts({
+          launcher: launcher.publicKey,
+          config: configPda,
+          campaign: campaignPda,
+          mint,
+          launcherAta,
+          vault: vaultPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([launcher])
+        .rpc();
+
+      const after = await program.account.campaignState.fetch(campaignPda);
+      assert.equal(after.fund.toString(), before.fund.add(topUpAmount).toString());
+      assert.equal(after.rewards[0].toString(), before.rewards[0].add(topUpAmount).toString());
+
+      const vaultAccount = await getAccount(provider.connection, vaultPda);
+      assert.equal(vaultAccount.amount.toString(), after.fund.toString());
+    });
+  });
+
+  describe("lock/unlock", () => {
+    it("should lock campaign successfully", async () => {
+      const tx = await program.methods
+        .lock()
+        .accounts({
+          distributor: distributor.publicKey,
+          config: configPda,
+          campaign: campaignPda,
+          mint
+        })
+        .signers([distributor])
+        .rpc();
+      
+      console.log("Lock transaction signature:", tx);
+      
+      // Verify campaign is locked
+      const campaignAccount = await program.account.campaignState.fetch(campaignPda);
+      assert.equal(campaignAccount.locked, 1, "Campaign should be locked");
+    });
+
+    it("should prevent claims when campaign is locked", async () => {
+      // First ensure campaign is locked
+      const campaignAccount = await program.account.campaignState.fetch(campaignPda);
+      assert.equal(campaignAccount.locked, 1, "Campaign should be locked for this test");
+
+      // Create a new claimer for this test
+      const testClaimer = Keypair.generate();
+      await provider.connection.requestAirdrop(testClaimer.publicKey, LAMPORTS_PER_SOL);
+      await new Promise(resolve => setTimeout(resolve, 1000));
+
+      const testClaimerAta = await getAssociatedTokenAddress(
+        mint,
+        testClaimer.publicKey
+      );
+
+      try {
+        await program.methods
+          .claim(
+            new anchor.BN(3), // unused index
+            [],
+            new anchor.BN(0),
+            claimAll,
+          )
+          .accounts({
+            claimer: testClaimer.publicKey,
+            launcher: launcher.publicKey,
+            config: configPda,
+            campaign: campaignPda,
+            mint,
+            vault: vaultPda,
+            claimerAta: testClaimerAta,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([testClaimer])
+          .rpc();
+        
+        assert.fail("Should have failed with CampaignLocked error");
+      } catch (error) {
+        assert.include(error.toString(), "CampaignLocked");
+      }
+    });
+
+    it("should only allow distributor to lock campaign", async () => {
+      const unauthorizedUser = Keypair.generate();
+      await provider.connection.requestAirdrop(unauthorizedUser.publicKey, LAMPORTS_PER_SOL);
+      await new Promise(resolve => setTimeout(resolve, 1000));
+
+      try {
+        await program.methods
+          .lock()
+          .accounts({
+            distributor: unauthorizedUser.publicKey,
+            config: configPda,
+            campaign: campaignPda,
+            mint
+          })
+          .signers([unauthorizedUser])
+          .rpc();
+        
+        assert.fail("Should have failed with InvalidDistributor error");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidDistributor");
+      }
+    });
+  });
+
+  describe("withdraw_unallocated", () => {
+    it("should withdraw unallocated funds while campaign is locked", async () => {
+      const withdrawAmount = new anchor.BN(1000000000);
+      const before = await program.account.campaignState.fetch(campaignPda);
+      assert.equal(before.locked, 1, "Campaign should be locked for this test");
+
+      await program.methods
+        .pause(8) // PAUSE_WITHDRAW
+        .accounts({
+          admin: admin.publicKey,
+          config: configPda,
+        })
+        .rpc();
+
+      try {
+        await program.methods
+          .withdrawUnallocated(withdrawAmount)
+          .accounts({
+            launcher: launcher.publicKey,
+            config: configPda,
+            campaign: campaignPda,
+            mint,
+            vault: vaultPda,
+            launcherAta,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([launcher])
+          .rpc();
+
+        assert.fail("Should have failed with ProgramPaused error");
+      } catch (error) {
+        assert.include(error.toString(), "ProgramPaused");
+      } finally {
+        await program.methods
+          .unpause(8)
+          .accounts({
+            admin: admin.publicKey,
+            config: configPda,
+          })
+          .rpc();
+      }
+
+      await program.methods
+        .withdrawUnallocated(withdrawAmount)
+        .accounts({
+          launcher: launcher.publicKey,
+          config: configPda,
+          campaign: campaignPda,
+          mint,
+          vault: vaultPda,
+          launcherAta,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([launcher])
+        .rpc();
+
+      const after = await program.account.campaignState.fetch(campaignPda);
+      assert.equal(after.fund.toString(), before.fund.sub(withdrawAmount).toString());
+      assert.equal(after.rewards[0].toString(), before.rewards[0].sub(withdrawAmount).toString());
+    });
+  });
+
+  describe("update", () => {
+    it("should update merkle root and rewards successfully", async () => {
+      const claimMerkleRoot = claimMerkleTree[claimMerkleTree.length - 1][0];
+      // Update rewards for claimer1 (index 1) and claimer2 (index 2)
+      const participants = [
+        [new anchor.BN(1), claimAmount1],
+        [new anchor.BN(2), claimAmount2]
+      ];
+      
+      const tx = await program.methods
+        .update(Array.from(claimMerkleRoot), participants)
+        .accounts({
+          distributor: distributor.publicKey,
+          config: configPda,
+          launcher: launcher.publicKey,
+          campaign: campaignPda,
+          mint
+        })
+        .signers([distributor])
+        .rpc();
+      
+      console.log("Update transaction signature:", tx);
+      await new Promise(resolve => setTimeout(resolve, 1000));
+      // Get transaction details for gas consumption
+      const txDetails = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0
+      });
+      const gasUsed = txDetails?.meta?.fee || 0;
+      console.log("Gas consumed:", gasUsed, "lamports");
+      
+      // Verify merkle root was updated and rewards were set
+      const campaignAccount = await program.account.campaignState.fetch(campaignPda);
+      assert.equal(
+        Buffer.from(campaignAccount.merkleRoot).toString("hex"),
+        claimMerkleRoot.toString("hex")
+      );
+      assert.equal(campaignAccount.rewards[1].toString(), claimAmount1.toString());
+      assert.equal(campaignAccount.rewards[2].toString(), claimAmount2.toString());
+    });
+    
+    it("should fail to withdraw unallocated funds while campaign is active", async () => {
+      try {
+        await program.methods
+          .withdrawUnallocated(new anchor.BN(1))
+          .accounts({
+            launcher: launcher.publicKey,
+            config: configPda,
+            campaign: campaignPda,
+            mint,
+            vault: vaultPda,
+            launcherAta,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([launcher])
+          .rpc();
+
+        assert.fail("Should have failed with CampaignNotLocked error");
+      } catch (error) {
+        assert.include(error.toString(), "CampaignNotLocked");
+      }
+    });
+
+    it("should resize rewards array and update in single transaction", async () => {
+      expandMerkleTree();
+      expandedMerkleTree = generateMerkleTree(merkleLeaves);
+      let expandedMerkleRoot = expandedMerkleTree[expandedMerkleTree.length - 1][0];
+      const participants: any[] = []; // No new participants in this test
+      
+      const campaignBefore = await program.account.campaignState.fetch(campaignPda);
+      const campaignLamportsBefore = await provider.connection.getBalance(campaignPda);
+
+      // Create both instructions
+      const extendIx = await program.methods
+        .extend(new anchor.BN(132)) // Extend to 132 participants (32 + 100)
+        .accounts({
+          distributor: distributor.publicKey,
+          config: configPda,
+          campaign: campaignPda,
+        })
+        .instruction();
+      
+      const updateIx = await program.methods
+        .update(Array.from(expandedMerkleRoot), participants)
+        .accounts({
+          distributor: distributor.publicKey,
+          config: configPda,
+          launcher: launcher.publicKey,
+          campaign: campaignPda,
+          mint
+        })
+        .instruction();
+      
+      // Send both instructions in a single transaction
+      const tx = new anchor.web3.Transaction().add(extendIx, updateIx);
+      const signature = await provider.sendAndConfirm(tx, [distributor]);      
+      
+      console.log("Update with resize transaction signature:", signature);
+      await new Promise(resolve => setTimeout(resolve, 1000));
+      // Get transaction details for gas consumption
+      const txDetails = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0
+      });
+      const gasUsed = txDetails?.meta?.fee || 0;
+      console.log("Gas consumed:", gasUsed, "lamports");
+      
+      // Verify rewards array was resized
+      const campaignAccount = await program.account.campaignState.fetch(campaignPda);
+      assert.equal(campaignAccount.rewards.length, 132); // Should be increased from 32 to 132 (32 + 100)
+      const campaignLamportsAfter = await provider.connection.getBalance(campaignPda);
+      assert.equal(
+        campaignAccount.reallocRent.sub(campaignBefore.reallocRent).toNumber(),
+        campaignLamportsAfter - campaignLamportsBefore,
+        "realloc_rent should record the lamports the distributor paid"
+      );
+      assert.equal(
+        Buffer.from(campaignAccount.merkleRoot).toString("hex"),
         expandedMerkleRoot.toString("hex")
       );
     });
-    
-    it("should fail with invalid distributor", async () => {
-      let claimMerkleRoot = claimMerkleTree[claimMerkleTree.length - 1][0];
-      const invalidDistributor = Keypair.generate();
-      await provider.connection.requestAirdrop(invalidDistributor.publicKey, LAMPORTS_PER_SOL);
-      await new Promise(resolve => setTimeout(resolve, 1000));
-      
+    
+    it("should not shrink a campaign with extend", async () => {
+      try {
+        await program.methods
+          .extend(new anchor.BN(32))
+          .accounts({
+            distributor: distributor.publicKey,
+            config: configPda,
+            campaign: campaignPda,
+          })
+          .signers([distributor])
+          .rpc();
+
+        assert.fail("Should have failed with CannotShrinkCampaign error");
+      } catch (error) {
+        assert.include(error.toString(), "CannotShrinkCampaign");
+      }
+
+      const campaignAccount = await program.account.campaignState.fetch(campaignPda);
+      assert.equal(campaignAccount.rewards.length, 132);
+    });
+
+    it("should fail with invalid distributor", async () => {
+      let claimMerkleRoot = claimMerkleTree[claimMerkleTree.length - 1][0];
+      const invalidDistributor = Keypair.generate();
+      await provider.connection.requestAirdrop(invalidDistributor.publicKey, LAMPORTS_PER_SOL);
+      await new Promise(resolve => setTimeout(resolve, 1000));
+      
+      try {
+        await program.methods
+          .update(Array.from(claimMerkleRoot), [])
+          .accounts({
+            distributor: invalidDistributor.publicKey,
+            config: configPda,
+            launcher: launcher.publicKey,
+            campaign: campaignPda,
+            mint
+          })
+          .signers([invalidDistributor])
+          .rpc();
+        
+        assert.fail("Should have failed with InvalidDistributor error");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidDistributor");
+      }
+    });
+
+    describe("assign_distributor", () => {
+      const secondDistributor = Keypair.generate();
+
+      async function updateAs(signer: Keypair) {
+        const expandedMerkleRoot = expandedMerkleTree[expandedMerkleTree.length - 1][0];
+        await program.methods
+          .update(Array.from(expandedMerkleRoot), [])
+          .accounts({
+            distributor: signer.publicKey,
+            config: configPda,
+            launcher: launcher.publicKey,
+            campaign: campaignPda,
+            mint
+          })
+          .signers([signer])
+          .rpc();
+      }
+
+      async function assignDistributor(newDistributor: PublicKey) {
+        await program.methods
+          .assignDistributor()
+          .accounts({
+            admin: admin.publicKey,
+            distributor: newDistributor,
+            config: configPda,
+            campaign: campaignPda,
+          })
+          .rpc();
+      }
+
+      before(async () => {
+        await provider.connection.requestAirdrop(secondDistributor.publicKey, LAMPORTS_PER_SOL);
+        await new Promise(resolve => setTimeout(resolve, 1000));
+        await program.methods
+          .proposeDistributor(secondDistributor.publicKey)
+          .accounts({
+            admin: admin.publicKey,
+            config: configPda,
+          })
+          .rpc();
+        await program.methods
+          .acceptDistributor()
+          .accounts({
+            pendingDistributor: secondDistributor.publicKey,
+            config: configPda,
+          })
+          .signers([secondDistributor])
+          .rpc();
+      });
+
+      it("should hand the campaign to another allowlisted distributor", async () => {
+        await assignDistributor(secondDistributor.publicKey);
+
+        const campaignAccount = await program.account.campaignState.fetch(campaignPda);
+        assert.equal(campaignAccount.distributor.toString(), secondDistributor.publicKey.toString());
+
+        try {
+          await updateAs(distributor);
+          assert.fail("Should have failed with InvalidDistributor error");
+        } catch (error) {
+          assert.include(error.toString(), "InvalidDistributor");
+        }
+
+        await updateAs(secondDistributor);
+      });
+
+      it("should reject a distributor outside the allowlist", async () => {
+        try {
+          await assignDistributor(Keypair.generate().publicKey);
+          assert.fail("Should have failed with InvalidDistributor error");
+        } catch (error) {
+          assert.include(error.toString(), "InvalidDistributor");
+        }
+
+        const campaignAccount = await program.account.campaignState.fetch(campaignPda);
+        assert.equal(campaignAccount.distributor.toString(), secondDistributor.publicKey.toString());
+      });
+
+      after(async () => {
+        // Later suites update the campaign as the original distributor
+        await assignDistributor(distributor.publicKey);
+        await program.methods
+          .removeDistributor(secondDistributor.publicKey)
+          .accounts({
+            admin: admin.publicKey,
+            config: configPda,
+          })
+          .rpc();
+      });
+    });
+  });
+
+  describe("claim", () => {
+    it("should fail when claims are paused", async () => {
+      const claimer1Ata = await getAssociatedTokenAddress(
+        mint,
+        claimer1.publicKey
+      );
+      let proof1 = getProof(expandedMerkleTree, 1);
+
+      await program.methods
+        .pause(4) // PAUSE_CLAIM
+        .accounts({
+          admin: admin.publicKey,
+          config: configPda,
+        })
+        .rpc();
+
+      try {
+        await program.methods
+          .claim(
+            new anchor.BN(1),
+            proof1.map(p => Array.from(p)),
+            merkleLeaves[1].nonce,
+            claimAll,
+          )
+          .accounts({
+            claimer: claimer1.publicKey,
+            launcher: launcher.publicKey,
+            config: configPda,
+            campaign: campaignPda,
+            mint,
+            vault: vaultPda,
+            claimerAta: claimer1Ata,
+            tokenProgram: TOKEN_PROGRAM_ID
+          })
+          .signers([claimer1])
+          .rpc();
+
+        assert.fail("Should have failed with ProgramPaused error");
+      } catch (error) {
+        assert.include(error.toString(), "ProgramPaused");
+      } finally {
+        await program.methods
+          .unpause(4)
+          .accounts({
+            admin: admin.publicKey,
+            config: configPda,
+          })
+          .rpc();
+      }
+
+      const configAccount = await program.account.config.fetch(configPda);
+      assert.equal(configAccount.paused, 0);
+    });
+
+    it("should fail before the claim window opens", async () => {
+      const scheduledSeed = new anchor.BN(launchTime + 2);
+      const nowTs = Math.floor(Date.now() / 1000);
+      const [scheduledCampaignPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("campaign"),
+          launcher.publicKey.toBuffer(),
+          mint.toBuffer(),
+          scheduledSeed.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      const scheduledVaultPda = await getAssociatedTokenAddress(mint, scheduledCampaignPda, true);
+
+      await program.methods
+        .launch(scheduledSeed, fundAmount, {
+          startTs: new anchor.BN(nowTs),
+          endTs: new anchor.BN(nowTs + 7200),
+          claimOpenTs: new anchor.BN(nowTs + 3600),
+          claimCloseTs: new anchor.BN(nowTs + 5400),
+          vestingCliff: new anchor.BN(0),
+          vestingDuration: new anchor.BN(0),
+          rootGraceSlots: new anchor.BN(0),
+        }, HASH_ALGO_SHA256)
+        .accounts({
+          launcher: launcher.publicKey,
+          distributor: distributor.publicKey,
+          feeReceiver: feeReceiver.publicKey,
+          config: configPda,
+          feeConfig: feeConfigPda,
+          campaign: scheduledCampaignPda,
+          mint,
+          launcherAta,
+          vault: scheduledVaultPda,
+          priceUpdate: pythPriceAccount.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([launcher])
+        .rpc();
+
+      try {
+        await program.methods
+          .claim(new anchor.BN(0), [], new anchor.BN(0), claimAll)
+          .accounts({
+            claimer: launcher.publicKey,
+            launcher: launcher.publicKey,
+            config: configPda,
+            campaign: scheduledCampaignPda,
+            mint,
+            vault: scheduledVaultPda,
+            claimerAta: launcherAta,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([launcher])
+          .rpc();
+
+        assert.fail("Should have failed with ClaimWindowNotOpen error");
+      } catch (error) {
+        assert.include(error.toString(), "ClaimWindowNotOpen");
+      }
+    });
+
+    describe("claim window", () => {
+      // Launches a campaign whose claim window opened a few seconds ago
+      async function launchWindowCampaign(seed: anchor.BN, claimCloseTs: number) {
+        const nowTs = Math.floor(Date.now() / 1000);
+        const [windowCampaignPda] = PublicKey.findProgramAddressSync(
+          [
+            Buffer.from("campaign"),
+            launcher.publicKey.toBuffer(),
+            mint.toBuffer(),
+            seed.toArrayLike(Buffer, "le", 8),
+          ],
+          program.programId
+        );
+        const windowVaultPda = await getAssociatedTokenAddress(mint, windowCampaignPda, true);
+
+        await program.methods
+          .launch(seed, fundAmount, {
+            startTs: new anchor.BN(nowTs - 5),
+            endTs: new anchor.BN(nowTs + 3600),
+            claimOpenTs: new anchor.BN(nowTs - 5),
+            claimCloseTs: new anchor.BN(claimCloseTs),
+            vestingCliff: new anchor.BN(0),
+            vestingDuration: new anchor.BN(0),
+            rootGraceSlots: new anchor.BN(0),
+          }, HASH_ALGO_SHA256)
+          .accounts({
+            launcher: launcher.publicKey,
+            distributor: distributor.publicKey,
+            feeReceiver: feeReceiver.publicKey,
+            config: configPda,
+            feeConfig: feeConfigPda,
+            campaign: windowCampaignPda,
+            mint,
+            launcherAta,
+            vault: windowVaultPda,
+            priceUpdate: pythPriceAccount.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([launcher])
+          .rpc();
+
+        return [windowCampaignPda, windowVaultPda];
+      }
+
+      it("should fail after the claim window closes", async () => {
+        const nowTs = Math.floor(Date.now() / 1000);
+        const [windowCampaignPda, windowVaultPda] = await launchWindowCampaign(new anchor.BN(launchTime + 11), nowTs + 2);
+        await new Promise(resolve => setTimeout(resolve, 5000));
+
+        try {
+          await program.methods
+            .claim(new anchor.BN(0), [], new anchor.BN(0), claimAll)
+            .accounts({
+              claimer: launcher.publicKey,
+              launcher: launcher.publicKey,
+              config: configPda,
+              campaign: windowCampaignPda,
+              mint,
+              vault: windowVaultPda,
+              claimerAta: launcherAta,
+              tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([launcher])
+            .rpc();
+
+          assert.fail("Should have failed with ClaimWindowClosed error");
+        } catch (error) {
+          assert.include(error.toString(), "ClaimWindowClosed");
+        }
+      });
+
+      it("should keep the claim window open when claim_close_ts is zero", async () => {
+        const [windowCampaignPda, windowVaultPda] = await launchWindowCampaign(new anchor.BN(launchTime + 12), 0);
+        const windowLeaves: MerkleLeaf[] = [];
+        for (let i = 0; i < 32; i++) {
+          windowLeaves.push({
+            claimer: i == 1 ? claimer1.publicKey : PublicKey.default,
+            amount: i == 1 ? claimAmount1 : new anchor.BN(0),
+            index: new anchor.BN(i),
+            nonce: new anchor.BN(i),
+          });
+        }
+        const windowTree = generateMerkleTree(windowLeaves);
+
+        await program.methods
+          .update(Array.from(windowTree[windowTree.length - 1][0]), [[new anchor.BN(1), claimAmount1]])
+          .accounts({
+            distributor: distributor.publicKey,
+            config: configPda,
+            launcher: launcher.publicKey,
+            campaign: windowCampaignPda,
+            mint
+          })
+          .signers([distributor])
+          .rpc();
+
+        const vaultBefore = (await getAccount(provider.connection, windowVaultPda)).amount;
+        await program.methods
+          .claim(new anchor.BN(1), getProof(windowTree, 1).map(p => Array.from(p)), new anchor.BN(1), claimAll)
+          .accounts({
+            claimer: claimer1.publicKey,
+            launcher: launcher.publicKey,
+            config: configPda,
+            campaign: windowCampaignPda,
+            mint,
+            vault: windowVaultPda,
+            claimerAta: await getAssociatedTokenAddress(mint, claimer1.publicKey),
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([claimer1])
+          .rpc();
+
+        const vaultAfter = (await getAccount(provider.connection, windowVaultPda)).amount;
+        assert.equal((vaultBefore - vaultAfter).toString(), claimAmount1.toString());
+      });
+    });
+
+    it("claimer1 should claim tokens successfully", async () => {
+      const claimer1Ata = await getAssociatedTokenAddress(
+        mint,
+        claimer1.publicKey
+      );
+      let proof1 = getProof(expandedMerkleTree, 1);
+
+      const tx = await program.methods
+        .claim(
+          new anchor.BN(1), // user_idx for claimer1
+          proof1.map(p => Array.from(p)),
+          merkleLeaves[1].nonce,
+          claimAll,
+        )
+        .accounts({
+          claimer: claimer1.publicKey,
+          launcher: launcher.publicKey,
+          config: configPda,
+          campaign: campaignPda,
+          mint,
+          vault: vaultPda,
+          claimerAta: claimer1Ata,
+          tokenProgram: TOKEN_PROGRAM_ID
+        })
+        .signers([claimer1])
+        .rpc();
+      
+      console.log("Claim transaction signature for claimer1:", tx);
+      await new Promise(resolve => setTimeout(resolve, 1000));
+      // Get transaction details for gas consumption
+      const txDetails = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0
+      });
+      const gasUsed = txDetails?.meta?.fee || 0;
+      console.log("Gas consumed:", gasUsed, "lamports");
+      
+      // Verify claimer received tokens
+      const claimerAccount = await getAccount(provider.connection, claimer1Ata);
+      assert.equal(claimerAccount.amount.toString(), claimAmount1.toString());
+      
+      // Verify the claim was recorded against the allocation
+      const campaignAccount = await program.account.campaignState.fetch(campaignPda);
+      assert.equal(campaignAccount.rewards[1].toString(), claimAmount1.toString());
+      assert.equal(campaignAccount.claimed[1].toString(), claimAmount1.toString());
+    });
+    
+    it("claimer1 should fail when trying to claim again", async () => {
+      const claimer1Ata = await getAssociatedTokenAddress(
+        mint,
+        claimer1.publicKey
+      );
+      let proof1 = getProof(expandedMerkleTree, 1);
+      try {
+        await program.methods
+          .claim(
+            new anchor.BN(1), // user_idx for claimer1
+            proof1.map(p => Array.from(p)),
+            merkleLeaves[1].nonce,
+            claimAll,
+          )
+          .accounts({
+            claimer: claimer1.publicKey,
+            launcher: launcher.publicKey,
+            config: configPda,
+            campaign: campaignPda,
+            mint,
+            vault: vaultPda,
+            claimerAta: claimer1Ata,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([claimer1])
+          .rpc();
+        
+        assert.fail("Should have failed with InvalidClaimAmount error (already claimed)");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidClaimAmount"); // Since claimed[1] now equals rewards[1]
+      }
+    });
+    
+    it("claimer2 should claim tokens successfully", async () => {
+      const claimer2Ata = await getAssociatedTokenAddress(
+        mint,
+        claimer2.publicKey
+      );
+      let proof2 = getProof(expandedMerkleTree, 2);
+      const tx = await program.methods
+        .claim(
+          new anchor.BN(2), // user_idx for claimer2
+          proof2.map(p => Array.from(p)),
+          merkleLeaves[2].nonce,
+          claimAll,
+        )
+        .accounts({
+          claimer: claimer2.publicKey,
+          launcher: launcher.publicKey,
+          config: configPda,
+          campaign: campaignPda,
+          mint,
+          vault: vaultPda,
+          claimerAta: claimer2Ata,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([claimer2])
+        .rpc();
+      
+      console.log("Claim transaction signature for claimer2:", tx);
+      await new Promise(resolve => setTimeout(resolve, 1000));
+      // Get transaction details for gas consumption
+      const txDetails = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0
+      });
+      const gasUsed = txDetails?.meta?.fee || 0;
+      console.log("Gas consumed:", gasUsed, "lamports");
+      
+      // Verify claimer received tokens
+      const claimerAccount = await getAccount(provider.connection, claimer2Ata);
+      assert.equal(claimerAccount.amount.toString(), claimAmount2.toString());
+      
+      // Verify rewards were updated (set to 0 after claim)
+      const campaignAccount = await program.account.campaignState.fetch(campaignPda);
+      assert.equal(campaignAccount.claimed[2].toString(), claimAmount2.toString());
+    });
+    
+    it("should not let the launcher claim the unallocated pool at index 0", async () => {
+      const proof0 = getProof(expandedMerkleTree, 0);
+      try {
+        await program.methods
+          .claim(
+            new anchor.BN(0),
+            proof0.map(p => Array.from(p)),
+            merkleLeaves[0].nonce,
+            claimAll,
+          )
+          .accounts({
+            claimer: launcher.publicKey,
+            launcher: launcher.publicKey,
+            config: configPda,
+            campaign: campaignPda,
+            mint,
+            vault: vaultPda,
+            claimerAta: launcherAta,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([launcher])
+          .rpc();
+
+        assert.fail("Should have failed with InvalidUserIdx error");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidUserIdx");
+      }
+    });
+
+    it("should fail with invalid proof", async () => {
+      const invalidClaimer = Keypair.generate();
+      await provider.connection.requestAirdrop(invalidClaimer.publicKey, LAMPORTS_PER_SOL);
+      await new Promise(resolve => setTimeout(resolve, 1000));
+      
+      const invalidClaimerAta = await getAssociatedTokenAddress(
+        mint,
+        invalidClaimer.publicKey
+      );
+      let proof1 = getProof(expandedMerkleTree, 1);
+      try {
+        await program.methods
+          .claim(
+            new anchor.BN(3), // user_idx 
+            proof1.map(p => Array.from(p)), // Wrong proof for this claimer
+            merkleLeaves[1].nonce,
+            claimAll,
+          )
+          .accounts({
+            claimer: invalidClaimer.publicKey,
+            launcher: launcher.publicKey,
+            config: configPda,
+            campaign: campaignPda,
+            mint,
+            vault: vaultPda,
+            claimerAta: invalidClaimerAta,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([invalidClaimer])
+          .rpc();
+        
+        assert.fail("Should have failed with InvalidClaimAmount error");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidClaimAmount");
+      }
+    });
+    
+    it("should fail with zero claim amount", async () => {
+      const zeroClaimer = Keypair.generate();
+      await provider.connection.requestAirdrop(zeroClaimer.publicKey, LAMPORTS_PER_SOL);
+      await new Promise(resolve => setTimeout(resolve, 1000));
+      
+      const zeroClaimerAta = await getAssociatedTokenAddress(
+        mint,
+        zeroClaimer.publicKey
+      );
+      
+      try {
+        await program.methods
+          .claim(
+            new anchor.BN(3), // user_idx with no rewards allocated
+            [],
+            new anchor.BN(0),
+            claimAll,
+          )
+          .accounts({
+            claimer: zeroClaimer.publicKey,
+            launcher: launcher.publicKey,
+            config: configPda,
+            campaign: campaignPda,
+            mint,
+            vault: vaultPda,
+            claimerAta: zeroClaimerAta,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([zeroClaimer])
+          .rpc();
+        
+        assert.fail("Should have failed with InvalidClaimAmount error");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidClaimAmount");
+      }
+    });
+
+    it("relayer should claim on behalf of a claimer without SOL", async () => {
+      const gaslessClaimer = Keypair.generate();
+      const gaslessAta = await getAssociatedTokenAddress(mint, gaslessClaimer.publicKey);
+      updateMerkleTree(3, gaslessClaimer.publicKey, claimAmount1);
+      const relayMerkleTree = generateMerkleTree(merkleLeaves);
+
+      await program.methods
+        .update(Array.from(relayMerkleTree[relayMerkleTree.length - 1][0]), [[new anchor.BN(3), claimAmount1]])
+        .accounts({
+          distributor: distributor.publicKey,
+          config: configPda,
+          launcher: launcher.publicKey,
+          campaign: campaignPda,
+          mint
+        })
+        .signers([distributor])
+        .rpc();
+
+      // The claimer authorizes campaign || user_idx || nonce || claimed || claim_amount off-chain
+      const relayAmount = claimAmount1.divn(2);
+      const claimed = (await program.account.campaignState.fetch(campaignPda)).claimed[3];
+      const message = Buffer.concat([
+        campaignPda.toBuffer(),
+        new anchor.BN(3).toArrayLike(Buffer, "le", 8),
+        merkleLeaves[3].nonce.toArrayLike(Buffer, "le", 8),
+        claimed.toArrayLike(Buffer, "le", 8),
+        relayAmount.toArrayLike(Buffer, "le", 8),
+      ]);
+      const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: gaslessClaimer.secretKey,
+        message,
+      });
+      const claimIx = await program.methods
+        .claimRelayed(
+          new anchor.BN(3),
+          getProof(relayMerkleTree, 3).map(p => Array.from(p)),
+          merkleLeaves[3].nonce,
+          relayAmount,
+        )
+        .accounts({
+          payer: admin.publicKey,
+          claimer: gaslessClaimer.publicKey,
+          launcher: launcher.publicKey,
+          config: configPda,
+          campaign: campaignPda,
+          mint,
+          vault: vaultPda,
+          claimerAta: gaslessAta,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .instruction();
+
+      await provider.sendAndConfirm(new anchor.web3.Transaction().add(ed25519Ix, claimIx));
+
+      const claimerAccount = await getAccount(provider.connection, gaslessAta);
+      assert.equal(claimerAccount.amount.toString(), relayAmount.toString());
+      assert.equal(await provider.connection.getBalance(gaslessClaimer.publicKey), 0);
+
+      // Half the allocation is still outstanding, but the signature was bound to the claimed amount.
+      // The compute budget instruction only keeps the replay from matching the first transaction's signature.
+      try {
+        const replayTx = new anchor.web3.Transaction().add(
+          ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }),
+          ed25519Ix,
+          claimIx,
+        );
+        await provider.sendAndConfirm(replayTx);
+        assert.fail("Should have failed with InvalidRelaySignature error");
+      } catch (error) {
+        assert.include([error.toString(), ...(error.logs ?? [])].join("\n"), "InvalidRelaySignature");
+      }
+    });
+
+    it("should send claimed tokens to an alternate recipient", async () => {
+      const coldWallet = Keypair.generate();
+      const coldWalletAta = await getAssociatedTokenAddress(mint, coldWallet.publicKey);
+      updateMerkleTree(4, claimer1.publicKey, claimAmount2);
+      const recipientMerkleTree = generateMerkleTree(merkleLeaves);
+
+      await program.methods
+        .update(Array.from(recipientMerkleTree[recipientMerkleTree.length - 1][0]), [[new anchor.BN(4), claimAmount2]])
+        .accounts({
+          distributor: distributor.publicKey,
+          config: configPda,
+          launcher: launcher.publicKey,
+          campaign: campaignPda,
+          mint
+        })
+        .signers([distributor])
+        .rpc();
+
+      await program.methods
+        .claim(
+          new anchor.BN(4),
+          getProof(recipientMerkleTree, 4).map(p => Array.from(p)),
+          merkleLeaves[4].nonce,
+          claimAll,
+        )
+        .accounts({
+          claimer: claimer1.publicKey,
+          launcher: launcher.publicKey,
+          config: configPda,
+          campaign: campaignPda,
+          mint,
+          vault: vaultPda,
+          claimerAta: null,
+          recipient: coldWallet.publicKey,
+          recipientAta: coldWalletAta,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([claimer1])
+        .rpc();
+
+      const recipientAccount = await getAccount(provider.connection, coldWalletAta);
+      assert.equal(recipientAccount.amount.toString(), claimAmount2.toString());
+    });
+
+    it("should batch claim several allocations in one instruction", async () => {
+      const claimer2Ata = await getAssociatedTokenAddress(mint, claimer2.publicKey);
+      updateMerkleTree(5, claimer2.publicKey, claimAmount1);
+      updateMerkleTree(6, claimer2.publicKey, claimAmount2);
+      const batchMerkleTree = generateMerkleTree(merkleLeaves);
+
+      await program.methods
+        .update(
+          Array.from(batchMerkleTree[batchMerkleTree.length - 1][0]),
+          [[new anchor.BN(5), claimAmount1], [new anchor.BN(6), claimAmount2]]
+        )
+        .accounts({
+          distributor: distributor.publicKey,
+          config: configPda,
+          launcher: launcher.publicKey,
+          campaign: campaignPda,
+          mint
+        })
+        .signers([distributor])
+        .rpc();
+
+      const balanceBefore = (await getAccount(provider.connection, claimer2Ata)).amount;
+      const claims = [5, 6].map(idx => ({
+        userIdx: new anchor.BN(idx),
+        proof: getProof(batchMerkleTree, idx).map(p => Array.from(p)),
+        nonce: merkleLeaves[idx].nonce,
+        claimAmount: claimAll,
+      }));
+      const group = [
+        { pubkey: campaignPda, isWritable: true, isSigner: false },
+        { pubkey: mint, isWritable: false, isSigner: false },
+        { pubkey: vaultPda, isWritable: true, isSigner: false },
+        { pubkey: claimer2Ata, isWritable: true, isSigner: false },
+      ];
+
+      await program.methods
+        .claimMany(claims)
+        .accounts({
+          claimer: claimer2.publicKey,
+          config: configPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts([...group, ...group])
+        .signers([claimer2])
+        .rpc();
+
+      const balanceAfter = (await getAccount(provider.connection, claimer2Ata)).amount;
+      assert.equal((balanceAfter - balanceBefore).toString(), claimAmount1.add(claimAmount2).toString());
+      const campaignAccount = await program.account.campaignState.fetch(campaignPda);
+      assert.equal(campaignAccount.claimed[5].toString(), claimAmount1.toString());
+      assert.equal(campaignAccount.claimed[6].toString(), claimAmount2.toString());
+    });
+
+    it("should claim part of an allocation and keep the rest", async () => {
+      const claimer2Ata = await getAssociatedTokenAddress(mint, claimer2.publicKey);
+      const remaining = claimAmount2.sub(claimAmount1);
+      updateMerkleTree(7, claimer2.publicKey, claimAmount2);
+      const partialMerkleTree = generateMerkleTree(merkleLeaves);
+
+      await program.methods
+        .update(Array.from(partialMerkleTree[partialMerkleTree.length - 1][0]), [[new anchor.BN(7), claimAmount2]])
+        .accounts({
+          distributor: distributor.publicKey,
+          config: configPda,
+          launcher: launcher.publicKey,
+          campaign: campaignPda,
+          mint
+        })
+        .signers([distributor])
+        .rpc();
+
+      const balanceBefore = (await getAccount(provider.connection, claimer2Ata)).amount;
+      await program.methods
+        .claim(
+          new anchor.BN(7),
+          getProof(partialMerkleTree, 7).map(p => Array.from(p)),
+          merkleLeaves[7].nonce,
+          claimAmount1,
+        )
+        .accounts({
+          claimer: claimer2.publicKey,
+          launcher: launcher.publicKey,
+          config: configPda,
+          campaign: campaignPda,
+          mint,
+          vault: vaultPda,
+          claimerAta: claimer2Ata,
+          recipient: null,
+          recipientAta: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([claimer2])
+        .rpc();
+
+      const balanceAfter = (await getAccount(provider.connection, claimer2Ata)).amount;
+      assert.equal((balanceAfter - balanceBefore).toString(), claimAmount1.toString());
+      const campaignAccount = await program.account.campaignState.fetch(campaignPda);
+      assert.equal(campaignAccount.rewards[7].toString(), claimAmount2.toString());
+      assert.equal(campaignAccount.claimed[7].toString(), claimAmount1.toString());
+      assert.equal(campaignAccount.rewards[7].sub(campaignAccount.claimed[7]).toString(), remaining.toString());
+    });
+
+    it("should claim the rest of a partial allocation with the same proof", async () => {
+      const claimer2Ata = await getAssociatedTokenAddress(mint, claimer2.publicKey);
+      const remaining = claimAmount2.sub(claimAmount1);
+      // No update since the partial claim, so the root and proof are unchanged
+      const partialMerkleTree = generateMerkleTree(merkleLeaves);
+
+      const balanceBefore = (await getAccount(provider.connection, claimer2Ata)).amount;
+      await program.methods
+        .claim(
+          new anchor.BN(7),
+          getProof(partialMerkleTree, 7).map(p => Array.from(p)),
+          merkleLeaves[7].nonce,
+          claimAll,
+        )
+        .accounts({
+          claimer: claimer2.publicKey,
+          launcher: launcher.publicKey,
+          config: configPda,
+          campaign: campaignPda,
+          mint,
+          vault: vaultPda,
+          claimerAta: claimer2Ata,
+          recipient: null,
+          recipientAta: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([claimer2])
+        .rpc();
+
+      const balanceAfter = (await getAccount(provider.connection, claimer2Ata)).amount;
+      assert.equal((balanceAfter - balanceBefore).toString(), remaining.toString());
+      const campaignAccount = await program.account.campaignState.fetch(campaignPda);
+      assert.equal(campaignAccount.claimed[7].toString(), claimAmount2.toString());
+
+      try {
+        await program.methods
+          .claim(
+            new anchor.BN(7),
+            getProof(partialMerkleTree, 7).map(p => Array.from(p)),
+            merkleLeaves[7].nonce,
+            claimAll,
+          )
+          .accounts({
+            claimer: claimer2.publicKey,
+            launcher: launcher.publicKey,
+            config: configPda,
+            campaign: campaignPda,
+            mint,
+            vault: vaultPda,
+            claimerAta: claimer2Ata,
+            recipient: null,
+            recipientAta: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([claimer2])
+          .rpc();
+
+        assert.fail("Should have failed with InvalidClaimAmount error (fully claimed)");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidClaimAmount");
+      }
+    });
+
+    it("should accept a proof against a recently replaced root", async () => {
+      const claimer1Ata = await getAssociatedTokenAddress(mint, claimer1.publicKey);
+      updateMerkleTree(8, claimer1.publicKey, claimAmount1);
+      const staleMerkleTree = generateMerkleTree(merkleLeaves);
+      await program.methods
+        .update(Array.from(staleMerkleTree[staleMerkleTree.length - 1][0]), [[new anchor.BN(8), claimAmount1]])
+        .accounts({
+          distributor: distributor.publicKey,
+          config: configPda,
+          launcher: launcher.publicKey,
+          campaign: campaignPda,
+          mint
+        })
+        .signers([distributor])
+        .rpc();
+
+      // A later update for another index replaces the root the claimer fetched a proof for
+      updateMerkleTree(9, claimer2.publicKey, claimAmount1);
+      const latestMerkleTree = generateMerkleTree(merkleLeaves);
+      await program.methods
+        .update(Array.from(latestMerkleTree[latestMerkleTree.length - 1][0]), [[new anchor.BN(9), claimAmount1]])
+        .accounts({
+          distributor: distributor.publicKey,
+          config: configPda,
+          launcher: launcher.publicKey,
+          campaign: campaignPda,
+          mint
+        })
+        .signers([distributor])
+        .rpc();
+
+      const balanceBefore = (await getAccount(provider.connection, claimer1Ata)).amount;
+      await program.methods
+        .claim(
+          new anchor.BN(8),
+          getProof(staleMerkleTree, 8).map(p => Array.from(p)),
+          merkleLeaves[8].nonce,
+          claimAll,
+        )
+        .accounts({
+          claimer: claimer1.publicKey,
+          launcher: launcher.publicKey,
+          config: configPda,
+          campaign: campaignPda,
+          mint,
+          vault: vaultPda,
+          claimerAta: claimer1Ata,
+          recipient: null,
+          recipientAta: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([claimer1])
+        .rpc();
+
+      const balanceAfter = (await getAccount(provider.connection, claimer1Ata)).amount;
+      assert.equal((balanceAfter - balanceBefore).toString(), claimAmount1.toString());
+    });
+  });
+
+  describe("vesting", () => {
+    const gaslessClaimer = Keypair.generate();
+    let vestingCampaignPda: PublicKey;
+    let vestingVaultPda: PublicKey;
+    let vestingTree: Buffer[][];
+
+    it("should vest claimed rewards and release the unlocked portion", async () => {
+      const vestingSeed = new anchor.BN(launchTime + 3);
+      const nowTs = Math.floor(Date.now() / 1000);
+      [vestingCampaignPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("campaign"),
+          launcher.publicKey.toBuffer(),
+          mint.toBuffer(),
+          vestingSeed.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      vestingVaultPda = await getAssociatedTokenAddress(mint, vestingCampaignPda, true);
+      const [vestingPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("vesting"), vestingCampaignPda.toBuffer(), claimer1.publicKey.toBuffer()],
+        program.programId
+      );
+      const claimer1Ata = await getAssociatedTokenAddress(mint, claimer1.publicKey);
+
+      await program.methods
+        .launch(vestingSeed, fundAmount, {
+          startTs: new anchor.BN(nowTs - 5),
+          endTs: new anchor.BN(nowTs + 3600),
+          claimOpenTs: new anchor.BN(nowTs - 5),
+          claimCloseTs: new anchor.BN(0),
+          vestingCliff: new anchor.BN(0),
+          vestingDuration: new anchor.BN(60),
+          rootGraceSlots: new anchor.BN(0),
+        }, HASH_ALGO_SHA256)
+        .accounts({
+          launcher: launcher.publicKey,
+          distributor: distributor.publicKey,
+          feeReceiver: feeReceiver.publicKey,
+          config: configPda,
+          feeConfig: feeConfigPda,
+          campaign: vestingCampaignPda,
+          mint,
+          launcherAta,
+          vault: vestingVaultPda,
+          priceUpdate: pythPriceAccount.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([launcher])
+        .rpc();
+
+      const vestingLeaves: MerkleLeaf[] = [];
+      for (let i = 0; i < 32; i++) {
+        vestingLeaves.push({
+          claimer: i == 1 ? claimer1.publicKey : i == 2 ? gaslessClaimer.publicKey : PublicKey.default,
+          amount: i == 1 || i == 2 ? claimAmount1 : new anchor.BN(0),
+          index: new anchor.BN(i),
+          nonce: new anchor.BN(i),
+        });
+      }
+      vestingTree = generateMerkleTree(vestingLeaves);
+
+      await program.methods
+        .update(
+          Array.from(vestingTree[vestingTree.length - 1][0]),
+          [[new anchor.BN(1), claimAmount1], [new anchor.BN(2), claimAmount1]],
+        )
+        .accounts({
+          distributor: distributor.publicKey,
+          config: configPda,
+          launcher: launcher.publicKey,
+          campaign: vestingCampaignPda,
+          mint
+        })
+        .signers([distributor])
+        .rpc();
+
+      const balanceBefore = (await getAccount(provider.connection, claimer1Ata)).amount;
+
+      await program.methods
+        .claim(new anchor.BN(1), getProof(vestingTree, 1).map(p => Array.from(p)), new anchor.BN(1), claimAll)
+        .accounts({
+          claimer: claimer1.publicKey,
+          launcher: launcher.publicKey,
+          config: configPda,
+          campaign: vestingCampaignPda,
+          mint,
+          vault: vestingVaultPda,
+          claimerAta: claimer1Ata,
+          vesting: vestingPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([claimer1])
+        .rpc();
+
+      // Claiming only records the entitlement
+      const vestingAccount = await program.account.vestingState.fetch(vestingPda);
+      assert.equal(vestingAccount.total.toString(), claimAmount1.toString());
+      assert.equal((await getAccount(provider.connection, claimer1Ata)).amount, balanceBefore);
+
+      await new Promise(resolve => setTimeout(resolve, 2000));
+      await program.methods
+        .withdrawVested()
+        .accounts({
+          claimer: claimer1.publicKey,
+          launcher: launcher.publicKey,
+          config: configPda,
+          campaign: vestingCampaignPda,
+          mint,
+          vesting: vestingPda,
+          vault: vestingVaultPda,
+          claimerAta: claimer1Ata,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([claimer1])
+        .rpc();
+
+      const released = (await getAccount(provider.connection, claimer1Ata)).amount - balanceBefore;
+      assert.isAbove(Number(released), 0, "Part of the reward should be unlocked");
+      assert.isBelow(Number(released), claimAmount1.toNumber(), "Reward should not be fully unlocked yet");
+    });
+
+    it("should relay a vesting claim without creating the claimer's token account", async () => {
+      const gaslessAta = await getAssociatedTokenAddress(mint, gaslessClaimer.publicKey);
+      const [gaslessVestingPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("vesting"), vestingCampaignPda.toBuffer(), gaslessClaimer.publicKey.toBuffer()],
+        program.programId
+      );
+      const message = Buffer.concat([
+        vestingCampaignPda.toBuffer(),
+        new anchor.BN(2).toArrayLike(Buffer, "le", 8),
+        new anchor.BN(2).toArrayLike(Buffer, "le", 8),
+        new anchor.BN(0).toArrayLike(Buffer, "le", 8),
+        claimAll.toArrayLike(Buffer, "le", 8),
+      ]);
+      const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: gaslessClaimer.secretKey,
+        message,
+      });
+      const claimIx = await program.methods
+        .claimRelayed(new anchor.BN(2), getProof(vestingTree, 2).map(p => Array.from(p)), new anchor.BN(2), claimAll)
+        .accounts({
+          payer: admin.publicKey,
+          claimer: gaslessClaimer.publicKey,
+          launcher: launcher.publicKey,
+          config: configPda,
+          campaign: vestingCampaignPda,
+          mint,
+          vault: vestingVaultPda,
+          claimerAta: null,
+          vesting: gaslessVestingPda,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .instruction();
+
+      await provider.sendAndConfirm(new anchor.web3.Transaction().add(ed25519Ix, claimIx));
+
+      const vestingAccount = await program.account.vestingState.fetch(gaslessVestingPda);
+      assert.equal(vestingAccount.total.toString(), claimAmount1.toString());
+      assert.isNull(await provider.connection.getAccountInfo(gaslessAta), "No token account should be created");
+    });
+  });
+
+  describe("keccak campaign", () => {
+    it("should verify claims against a keccak256 merkle tree", async () => {
+      const keccakSeed = new anchor.BN(launchTime + 4);
+      const [keccakCampaignPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("campaign"),
+          launcher.publicKey.toBuffer(),
+          mint.toBuffer(),
+          keccakSeed.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      const keccakVaultPda = await getAssociatedTokenAddress(mint, keccakCampaignPda, true);
+      const claimer1Ata = await getAssociatedTokenAddress(mint, claimer1.publicKey);
+
+      await program.methods
+        .launch(keccakSeed, fundAmount, campaignSchedule, HASH_ALGO_KECCAK256)
+        .accounts({
+          launcher: launcher.publicKey,
+          distributor: distributor.publicKey,
+          feeReceiver: feeReceiver.publicKey,
+          config: configPda,
+          feeConfig: feeConfigPda,
+          campaign: keccakCampaignPda,
+          mint,
+          launcherAta,
+          vault: keccakVaultPda,
+          priceUpdate: pythPriceAccount.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([launcher])
+        .rpc();
+
+      const keccakLeaves: MerkleLeaf[] = [];
+      for (let i = 0; i < 32; i++) {
+        keccakLeaves.push({
+          claimer: i == 1 ? claimer1.publicKey : PublicKey.default,
+          amount: i == 1 ? claimAmount1 : new anchor.BN(0),
+          index: new anchor.BN(i),
+          nonce: new anchor.BN(i),
+        });
+      }
+      const keccakTree = generateMerkleTree(keccakLeaves, keccak_256);
+
+      await program.methods
+        .update(Array.from(keccakTree[keccakTree.length - 1][0]), [[new anchor.BN(1), claimAmount1]])
+        .accounts({
+          distributor: distributor.publicKey,
+          config: configPda,
+          launcher: launcher.publicKey,
+          campaign: keccakCampaignPda,
+          mint
+        })
+        .signers([distributor])
+        .rpc();
+
+      const balanceBefore = (await getAccount(provider.connection, claimer1Ata)).amount;
+      await program.methods
+        .claim(new anchor.BN(1), getProof(keccakTree, 1).map(p => Array.from(p)), new anchor.BN(1), claimAll)
+        .accounts({
+          claimer: claimer1.publicKey,
+          launcher: launcher.publicKey,
+          config: configPda,
+          campaign: keccakCampaignPda,
+          mint,
+          vault: keccakVaultPda,
+          claimerAta: claimer1Ata,
+          recipient: null,
+          recipientAta: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([claimer1])
+        .rpc();
+
+      const balanceAfter = (await getAccount(provider.connection, claimer1Ata)).amount;
+      assert.equal((balanceAfter - balanceBefore).toString(), claimAmount1.toString());
+    });
+  });
+
+  describe("legacy campaign migration", () => {
+    // Campaign written by the first release, preloaded from tests/fixtures through Anchor.toml
+    const legacyFixture = JSON.parse(fs.readFileSync("./tests/fixtures/legacy-campaign.json", "utf-8"));
+    const legacyData = Buffer.from(legacyFixture.account.data[0], "base64");
+    const legacyCampaignPda = new PublicKey(legacyFixture.pubkey);
+    const legacyLauncher = new PublicKey(legacyData.subarray(1, 33));
+    const legacyMint = Keypair.fromSecretKey(
+      new Uint8Array(JSON.parse(fs.readFileSync("./tests/fixtures/legacy-mint.json", "utf-8")))
+    );
+    const legacyClaimer = Keypair.fromSecretKey(
+      new Uint8Array(JSON.parse(fs.readFileSync("./tests/fixtures/legacy-claimer.json", "utf-8")))
+    );
+    const legacyFund = new anchor.BN(1000000000);
+    const legacyAmount = new anchor.BN(600000000);
+    const legacyNonce = new anchor.BN(42);
+
+    // Legacy trees hash leaves and nodes without the 0x00 / 0x01 prefixes
+    function legacyHash(...parts: Buffer[]): Buffer {
+      return Buffer.from(sha256.array(Buffer.concat(parts)));
+    }
+
+    function legacyTree(): Buffer[][] {
+      const leaves: Buffer[] = [];
+      for (let i = 0; i < 32; i++) {
+        leaves.push(legacyHash(
+          (i == 1 ? legacyClaimer.publicKey : PublicKey.default).toBuffer(),
+          (i == 1 ? legacyAmount : new anchor.BN(0)).toArrayLike(Buffer, "le", 8),
+          new anchor.BN(i).toArrayLike(Buffer, "le", 8),
+          (i == 1 ? legacyNonce : new anchor.BN(0)).toArrayLike(Buffer, "le", 8),
+        ));
+      }
+      const layers = [leaves];
+      while (layers[layers.length - 1].length > 1) {
+        const level = layers[layers.length - 1];
+        const next: Buffer[] = [];
+        for (let i = 0; i < level.length; i += 2) {
+          next.push(legacyHash(level[i], level[i + 1]));
+        }
+        layers.push(next);
+      }
+      return layers;
+    }
+
+    it("should migrate a legacy campaign and keep unprefixed hashing", async () => {
+      await program.methods
+        .migrateCampaign()
+        .accounts({
+          admin: admin.publicKey,
+          distributor: distributor.publicKey,
+          config: configPda,
+          campaign: legacyCampaignPda,
+        })
+        .signers([distributor])
+        .rpc();
+
+      const campaignAccount = await program.account.campaignState.fetch(legacyCampaignPda);
+      const tree = legacyTree();
+      assert.equal(campaignAccount.hashVersion, 0, "Migrated campaigns should keep legacy hashing");
+      assert.equal(campaignAccount.launcher.toString(), legacyLauncher.toString());
+      assert.equal(campaignAccount.mint.toString(), legacyMint.publicKey.toString());
+      assert.equal(campaignAccount.distributor.toString(), distributor.publicKey.toString());
+      assert.equal(campaignAccount.rewards[0].toString(), legacyFund.sub(legacyAmount).toString());
+      assert.equal(campaignAccount.rewards[1].toString(), legacyAmount.toString());
+      assert.isTrue(campaignAccount.claimed.every(claimed => claimed.isZero()));
+      assert.equal(
+        Buffer.from(campaignAccount.merkleRoot).toString("hex"),
+        tree[tree.length - 1][0].toString("hex")
+      );
+    });
+
+    it("should not migrate a campaign twice", async () => {
+      try {
+        await program.methods
+          .migrateCampaign()
+          .accounts({
+            admin: admin.publicKey,
+            distributor: distributor.publicKey,
+            config: configPda,
+            campaign: legacyCampaignPda,
+          })
+          .signers([distributor])
+          .rpc();
+
+        assert.fail("Should have failed with NotLegacyCampaign error");
+      } catch (error) {
+        assert.include(error.toString(), "NotLegacyCampaign");
+      }
+    });
+
+    it("should claim from a migrated campaign with a legacy proof", async () => {
+      await createMint(provider.connection, launcher, launcher.publicKey, null, 6, legacyMint);
+      const legacyVault = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        launcher,
+        legacyMint.publicKey,
+        legacyCampaignPda,
+        true
+      );
+      await mintTo(provider.connection, launcher, legacyMint.publicKey, legacyVault.address, launcher, BigInt(legacyFund.toString()));
+      const legacyClaimerAta = await createAssociatedTokenAccount(
+        provider.connection,
+        launcher,
+        legacyMint.publicKey,
+        legacyClaimer.publicKey
+      );
+
+      await program.methods
+        .claim(new anchor.BN(1), getProof(legacyTree(), 1).map(p => Array.from(p)), legacyNonce, claimAll)
+        .accounts({
+          claimer: legacyClaimer.publicKey,
+          launcher: legacyLauncher,
+          config: configPda,
+          campaign: legacyCampaignPda,
+          mint: legacyMint.publicKey,
+          vault: legacyVault.address,
+          claimerAta: legacyClaimerAta,
+          recipient: null,
+          recipientAta: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([legacyClaimer])
+        .rpc();
+
+      const claimerAccount = await getAccount(provider.connection, legacyClaimerAta);
+      assert.equal(claimerAccount.amount.toString(), legacyAmount.toString());
+    });
+  });
+
+  describe("stablecoin service fee", () => {
+    let usdMint: PublicKey;
+    let launcherUsdAta: PublicKey;
+
+    function stablecoinCampaign(seed: anchor.BN, campaignMint: PublicKey) {
+      const [campaign] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("campaign"),
+          launcher.publicKey.toBuffer(),
+          campaignMint.toBuffer(),
+          seed.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      return campaign;
+    }
+
+    before(async () => {
+      usdMint = await createMint(provider.connection, launcher, launcher.publicKey, null, 6);
+      launcherUsdAta = await createAssociatedTokenAccount(provider.connection, launcher, usdMint, launcher.publicKey);
+      await mintTo(
+        provider.connection,
+        launcher,
+        usdMint,
+        launcherUsdAta,
+        launcher,
+        BigInt(fundAmount.toString()) + BigInt(10_000_000)
+      );
+
+      await program.methods
+        .updateFeeConfig(feeReceiver.publicKey, new anchor.BN(500), [], [usdMint])
+        .accounts({
+          admin: admin.publicKey,
+          config: configPda,
+          feeConfig: feeConfigPda,
+        })
+        .rpc();
+    });
+
+    it("should reject a fee mint outside the whitelist", async () => {
+      const seed = new anchor.BN(launchTime + 5);
+      const campaign = stablecoinCampaign(seed, mint);
       try {
         await program.methods
-          .update(Array.from(claimMerkleRoot), [])
+          .launch(seed, fundAmount, campaignSchedule, HASH_ALGO_SHA256)
           .accounts({
-            distributor: invalidDistributor.publicKey,
             launcher: launcher.publicKey,
-            campaign: campaignPda,
-            mint
+            distributor: distributor.publicKey,
+            feeReceiver: feeReceiver.publicKey,
+            config: configPda,
+            feeConfig: feeConfigPda,
+            campaign,
+            mint,
+            launcherAta,
+            vault: await getAssociatedTokenAddress(mint, campaign, true),
+            priceUpdate: pythPriceAccount.publicKey,
+            feeMint: mint,
+            launcherFeeAta: launcherAta,
+            feeReceiverAta: await getAssociatedTokenAddress(mint, feeReceiver.publicKey),
+            tokenProgram: TOKEN_PROGRAM_ID,
           })
-          .signers([invalidDistributor])
+          .signers([launcher])
           .rpc();
-        
-        assert.fail("Should have failed with InvalidDistributor error");
+
+        assert.fail("Should have failed with InvalidFeeMint error");
       } catch (error) {
-        assert.include(error.toString(), "InvalidDistributor");
+        assert.include(error.toString(), "InvalidFeeMint");
       }
     });
-  });
 
-  describe("claim", () => {
-    it("claimer1 should claim tokens successfully", async () => {
-      const claimer1Ata = await getAssociatedTokenAddress(
-        mint,
-        claimer1.publicKey
-      );
-      let proof1 = getProof(expandedMerkleTree, 1);
+    it("should charge the fee in the campaign stablecoin instead of SOL", async () => {
+      const seed = new anchor.BN(launchTime + 6);
+      const campaign = stablecoinCampaign(seed, usdMint);
+      const feeReceiverUsdAta = await getAssociatedTokenAddress(usdMint, feeReceiver.publicKey);
 
-      const tx = await program.methods
-        .claim(
-          new anchor.BN(1), // user_idx for claimer1
-          proof1.map(p => Array.from(p)),
-          merkleLeaves[1].nonce,
-        )
+      const quotedFee = await program.methods
+        .quoteFee(launcher.publicKey)
+        .accounts({
+          config: configPda,
+          feeConfig: feeConfigPda,
+          priceUpdate: pythPriceAccount.publicKey,
+          fallbackPriceUpdate: null,
+          feeMint: usdMint,
+        })
+        .view();
+      assert.equal(quotedFee.toNumber(), 5_000_000);
+
+      const feeReceiverLamportsBefore = await provider.connection.getBalance(feeReceiver.publicKey);
+      await program.methods
+        .launch(seed, fundAmount, campaignSchedule, HASH_ALGO_SHA256)
         .accounts({
-          claimer: claimer1.publicKey,
           launcher: launcher.publicKey,
-          campaign: campaignPda,
-          mint,
-          vault: vaultPda,
-          claimerAta: claimer1Ata,
-          tokenProgram: TOKEN_PROGRAM_ID
+          distributor: distributor.publicKey,
+          feeReceiver: feeReceiver.publicKey,
+          config: configPda,
+          feeConfig: feeConfigPda,
+          campaign,
+          mint: usdMint,
+          launcherAta: launcherUsdAta,
+          vault: await getAssociatedTokenAddress(usdMint, campaign, true),
+          priceUpdate: pythPriceAccount.publicKey,
+          feeMint: usdMint,
+          launcherFeeAta: launcherUsdAta,
+          feeReceiverAta: feeReceiverUsdAta,
+          tokenProgram: TOKEN_PROGRAM_ID,
         })
-        .signers([claimer1])
+        .signers([launcher])
         .rpc();
-      
-      console.log("Claim transaction signature for claimer1:", tx);
-      await new Promise(resolve => setTimeout(resolve, 1000));
-      // Get transaction details for gas consumption
-      const txDetails = await provider.connection.getTransaction(tx, {
-        commitment: "confirmed",
-        maxSupportedTransactionVersion: 0
-      });
-      const gasUsed = txDetails?.meta?.fee || 0;
-      console.log("Gas consumed:", gasUsed, "lamports");
-      
-      // Verify claimer received tokens
-      const claimerAccount = await getAccount(provider.connection, claimer1Ata);
-      assert.equal(claimerAccount.amount.toString(), claimAmount1.toString());
-      
-      // Verify rewards were updated (set to 0 after claim)
-      const campaignAccount = await program.account.campaignState.fetch(campaignPda);
-      assert.equal(campaignAccount.rewards[1].toString(), "0"); // Should be 0 after claim
+
+      const feeReceiverUsd = await getAccount(provider.connection, feeReceiverUsdAta);
+      assert.equal(feeReceiverUsd.amount.toString(), "5000000");
+      assert.equal(await provider.connection.getBalance(feeReceiver.publicKey), feeReceiverLamportsBefore);
+
+      const launcherUsd = await getAccount(provider.connection, launcherUsdAta);
+      assert.equal(launcherUsd.amount.toString(), "5000000");
     });
-    
-    it("claimer1 should fail when trying to claim again", async () => {
-      const claimer1Ata = await getAssociatedTokenAddress(
-        mint,
-        claimer1.publicKey
-      );
-      let proof1 = getProof(expandedMerkleTree, 1);
+  });
+
+  describe("clawback", () => {
+    let shortCampaignPda: PublicKey;
+    let shortVaultPda: PublicKey;
+
+    it("should fail before the campaign ends", async () => {
       try {
         await program.methods
-          .claim(
-            new anchor.BN(1), // user_idx for claimer1
-            proof1.map(p => Array.from(p)),
-            merkleLeaves[1].nonce,
-          )
+          .clawback()
           .accounts({
-            claimer: claimer1.publicKey,
             launcher: launcher.publicKey,
+            config: configPda,
             campaign: campaignPda,
             mint,
             vault: vaultPda,
-            claimerAta: claimer1Ata,
+            launcherAta,
             tokenProgram: TOKEN_PROGRAM_ID,
           })
-          .signers([claimer1])
+          .signers([launcher])
           .rpc();
-        
-        assert.fail("Should have failed with InvalidClaimAmount error (already claimed)");
+
+        assert.fail("Should have failed with CampaignNotEnded error");
       } catch (error) {
-        assert.include(error.toString(), "InvalidClaimAmount"); // Since rewards[1] is now 0
+        assert.include(error.toString(), "CampaignNotEnded");
       }
     });
-    
-    it("claimer2 should claim tokens successfully", async () => {
-      const claimer2Ata = await getAssociatedTokenAddress(
-        mint,
-        claimer2.publicKey
+
+    it("should return the vault balance to the launcher after expiry", async () => {
+      const shortSeed = new anchor.BN(launchTime + 1);
+      const shortEndTs = new anchor.BN(Math.floor(Date.now() / 1000) + 2);
+      [shortCampaignPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("campaign"),
+          launcher.publicKey.toBuffer(),
+          mint.toBuffer(),
+          shortSeed.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
       );
-      let proof2 = getProof(expandedMerkleTree, 2);
-      const tx = await program.methods
-        .claim(
-          new anchor.BN(2), // user_idx for claimer2
-          proof2.map(p => Array.from(p)),
-          merkleLeaves[2].nonce,
-        )
+      shortVaultPda = await getAssociatedTokenAddress(mint, shortCampaignPda, true);
+
+      await program.methods
+        .launch(shortSeed, fundAmount, { ...campaignSchedule, endTs: shortEndTs }, HASH_ALGO_SHA256)
         .accounts({
-          claimer: claimer2.publicKey,
           launcher: launcher.publicKey,
-          campaign: campaignPda,
+          distributor: distributor.publicKey,
+          feeReceiver: feeReceiver.publicKey,
+          config: configPda,
+          feeConfig: feeConfigPda,
+          campaign: shortCampaignPda,
           mint,
-          vault: vaultPda,
-          claimerAta: claimer2Ata,
+          launcherAta,
+          vault: shortVaultPda,
+          priceUpdate: pythPriceAccount.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
         })
-        .signers([claimer2])
+        .signers([launcher])
         .rpc();
-      
-      console.log("Claim transaction signature for claimer2:", tx);
-      await new Promise(resolve => setTimeout(resolve, 1000));
-      // Get transaction details for gas consumption
-      const txDetails = await provider.connection.getTransaction(tx, {
-        commitment: "confirmed",
-        maxSupportedTransactionVersion: 0
-      });
-      const gasUsed = txDetails?.meta?.fee || 0;
-      console.log("Gas consumed:", gasUsed, "lamports");
-      
-      // Verify claimer received tokens
-      const claimerAccount = await getAccount(provider.connection, claimer2Ata);
-      assert.equal(claimerAccount.amount.toString(), claimAmount2.toString());
-      
-      // Verify rewards were updated (set to 0 after claim)
-      const campaignAccount = await program.account.campaignState.fetch(campaignPda);
-      assert.equal(campaignAccount.rewards[2].toString(), "0"); // Should be 0 after claim
+
+      // Wait for the campaign to expire
+      await new Promise(resolve => setTimeout(resolve, 4000));
+      const launcherBalanceBefore = (await getAccount(provider.connection, launcherAta)).amount;
+
+      await program.methods
+        .pause(8) // PAUSE_WITHDRAW
+        .accounts({
+          admin: admin.publicKey,
+          config: configPda,
+        })
+        .rpc();
+
+      try {
+        await program.methods
+          .clawback()
+          .accounts({
+            launcher: launcher.publicKey,
+            config: configPda,
+            campaign: shortCampaignPda,
+            mint,
+            vault: shortVaultPda,
+            launcherAta,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([launcher])
+          .rpc();
+
+        assert.fail("Should have failed with ProgramPaused error");
+      } catch (error) {
+        assert.include(error.toString(), "ProgramPaused");
+      } finally {
+        await program.methods
+          .unpause(8)
+          .accounts({
+            admin: admin.publicKey,
+            config: configPda,
+          })
+          .rpc();
+      }
+
+      await program.methods
+        .clawback()
+        .accounts({
+          launcher: launcher.publicKey,
+          config: configPda,
+          campaign: shortCampaignPda,
+          mint,
+          vault: shortVaultPda,
+          launcherAta,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([launcher])
+        .rpc();
+
+      const launcherBalanceAfter = (await getAccount(provider.connection, launcherAta)).amount;
+      assert.equal((launcherBalanceAfter - launcherBalanceBefore).toString(), fundAmount.toString());
+
+      const campaignAccount = await program.account.campaignState.fetch(shortCampaignPda);
+      assert.equal(campaignAccount.closed, 1);
+      assert.equal(campaignAccount.rewards[0].toString(), "0");
     });
-    
-    it("should fail with invalid proof", async () => {
-      const invalidClaimer = Keypair.generate();
-      await provider.connection.requestAirdrop(invalidClaimer.publicKey, LAMPORTS_PER_SOL);
-      await new Promise(resolve => setTimeout(resolve, 1000));
-      
-      const invalidClaimerAta = await getAssociatedTokenAddress(
-        mint,
-        invalidClaimer.publicKey
-      );
-      let proof1 = getProof(expandedMerkleTree, 1);
+
+    it("should close a clawed back campaign and reclaim rent", async () => {
+      // Dust sent after clawback must not keep the campaign open
+      await mintTo(provider.connection, launcher, mint, shortVaultPda, launcher, 1);
+      const launcherBalanceBefore = (await getAccount(provider.connection, launcherAta)).amount;
+      const launcherLamportsBefore = await provider.connection.getBalance(launcher.publicKey);
+
+      await program.methods
+        .pause(8) // PAUSE_WITHDRAW
+        .accounts({
+          admin: admin.publicKey,
+          config: configPda,
+        })
+        .rpc();
+
       try {
         await program.methods
-          .claim(
-            new anchor.BN(3), // user_idx 
-            proof1.map(p => Array.from(p)), // Wrong proof for this claimer
-            merkleLeaves[1].nonce,
-          )
+          .closeCampaign()
           .accounts({
-            claimer: invalidClaimer.publicKey,
             launcher: launcher.publicKey,
-            campaign: campaignPda,
+            distributor: distributor.publicKey,
+            config: configPda,
+            campaign: shortCampaignPda,
             mint,
-            vault: vaultPda,
-            claimerAta: invalidClaimerAta,
+            vault: shortVaultPda,
+            launcherAta,
             tokenProgram: TOKEN_PROGRAM_ID,
           })
-          .signers([invalidClaimer])
+          .signers([launcher])
           .rpc();
-        
-        assert.fail("Should have failed with InvalidClaimAmount error");
+
+        assert.fail("Should have failed with ProgramPaused error");
       } catch (error) {
-        assert.include(error.toString(), "InvalidClaimAmount");
+        assert.include(error.toString(), "ProgramPaused");
+      } finally {
+        await program.methods
+          .unpause(8)
+          .accounts({
+            admin: admin.publicKey,
+            config: configPda,
+          })
+          .rpc();
       }
+
+      await program.methods
+        .closeCampaign()
+        .accounts({
+          launcher: launcher.publicKey,
+          distributor: distributor.publicKey,
+          config: configPda,
+          campaign: shortCampaignPda,
+          mint,
+          vault: shortVaultPda,
+          launcherAta,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([launcher])
+        .rpc();
+
+      const launcherLamportsAfter = await provider.connection.getBalance(launcher.publicKey);
+      assert.isAbove(launcherLamportsAfter, launcherLamportsBefore, "Rent should be returned to launcher");
+      const launcherBalanceAfter = (await getAccount(provider.connection, launcherAta)).amount;
+      assert.equal((launcherBalanceAfter - launcherBalanceBefore).toString(), "1", "Dust should be swept to the launcher");
+      assert.isNull(await provider.connection.getAccountInfo(shortCampaignPda));
+      assert.isNull(await provider.connection.getAccountInfo(shortVaultPda));
     });
-    
-    it("should fail with zero claim amount", async () => {
-      const zeroClaimer = Keypair.generate();
-      await provider.connection.requestAirdrop(zeroClaimer.publicKey, LAMPORTS_PER_SOL);
-      await new Promise(resolve => setTimeout(resolve, 1000));
-      
-      const zeroClaimerAta = await getAssociatedTokenAddress(
-        mint,
-        zeroClaimer.publicKey
-      );
-      
+
+    it("should fail to close a campaign with outstanding rewards", async () => {
       try {
         await program.methods
-          .claim(
-            new anchor.BN(3), // user_idx with no rewards allocated
-            [],
-            new anchor.BN(0)
-          )
+          .closeCampaign()
           .accounts({
-            claimer: zeroClaimer.publicKey,
             launcher: launcher.publicKey,
+            distributor: distributor.publicKey,
+            config: configPda,
             campaign: campaignPda,
             mint,
             vault: vaultPda,
-            claimerAta: zeroClaimerAta,
+            launcherAta,
             tokenProgram: TOKEN_PROGRAM_ID,
           })
-          .signers([zeroClaimer])
+          .signers([launcher])
           .rpc();
-        
-        assert.fail("Should have failed with InvalidClaimAmount error");
+
+        assert.fail("Should have failed with CampaignNotEmpty error");
       } catch (error) {
-        assert.include(error.toString(), "InvalidClaimAmount");
+        assert.include(error.toString(), "CampaignNotEmpty");
       }
     });
   });
-});
\ No newline at end of file
+});